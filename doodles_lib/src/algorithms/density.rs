@@ -0,0 +1,199 @@
+//! Provides a histogram of the paths of particles, rendered with tone mapping.
+//!
+//! Drawing many translucent lines on top of each other quickly saturates and the result
+//! cannot be changed afterwards. A [`DensityAccumulator`] instead counts how often the paths
+//! pass through each pixel, optionally together with their colors, in a floating point buffer.
+//! The counts are turned into an image only at the end, with a [`ToneMapping`] which compresses
+//! their huge range, the way fractal flames are rendered. The accumulator doesn't depend on a
+//! window, so it can be used headless.
+use crate::color::Gradient;
+use nannou::{
+    color::{rgb8, Rgb8},
+    geom::{Point2, Rect},
+    image::{Rgb, RgbImage},
+};
+use ndarray::{Array, Ix2, Ix3};
+
+/// Enumeration of the curves which map a density to an intensity in the range [0..1].
+///
+/// Both curves divide by the largest density and raise the result to the power of 1 / gamma.
+#[derive(Debug, Copy, Clone)]
+pub enum ToneMapping {
+    Linear {
+        gamma: f32,
+    },
+    /// Maps a density d to log(1 + exposure * d), which brings out the faint paths
+    /// without saturating the dense ones. An exposure which isn't positive falls back to
+    /// the linear curve.
+    Logarithmic {
+        exposure: f32,
+        gamma: f32,
+    },
+}
+
+impl ToneMapping {
+    pub fn apply(&self, density: f32, max_density: f32) -> f32 {
+        if max_density <= 0.0 {
+            return 0.0;
+        }
+
+        let (intensity, gamma) = match self {
+            ToneMapping::Linear { gamma } => (density / max_density, gamma),
+            ToneMapping::Logarithmic { exposure, gamma } if *exposure <= 0.0 => {
+                (density / max_density, gamma)
+            }
+            ToneMapping::Logarithmic { exposure, gamma } => (
+                (1.0 + exposure * density).ln() / (1.0 + exposure * max_density).ln(),
+                gamma,
+            ),
+        };
+
+        intensity.clamp(0.0, 1.0).powf(1.0 / gamma)
+    }
+}
+
+/// Enumeration of the ways in which intensities are turned into colors.
+#[derive(Debug, Clone)]
+pub enum Coloring {
+    /// Looks up the intensity of each pixel in a gradient.
+    Palette(Gradient),
+    /// Blends the average color of the paths through each pixel over the background,
+    /// by the pixel's intensity. Accumulators without color channels use white.
+    Channels { background: Rgb8 },
+}
+
+/// Counts the passes of paths through the pixels of an image laid over a canvas.
+///
+/// The internal arrays are indexed by column and row, starting from the bottom left pixel.
+pub struct DensityAccumulator {
+    canvas: Rect,
+    width: usize,
+    height: usize,
+    density: Array<f32, Ix2>,
+    colors: Option<Array<f32, Ix3>>,
+}
+
+impl DensityAccumulator {
+    /// Constructs a new, empty, instance of [`DensityAccumulator`], which renders the canvas
+    /// into an image of width by height pixels.
+    pub fn new(canvas: Rect, width: usize, height: usize) -> Self {
+        Self {
+            canvas,
+            width,
+            height,
+            density: Array::<f32, Ix2>::zeros((width, height)),
+            colors: None,
+        }
+    }
+
+    /// Makes the accumulator sum up the colors of the paths, see [`Coloring::Channels`].
+    pub fn with_color_channels(mut self) -> Self {
+        self.colors = Some(Array::<f32, Ix3>::zeros((self.width, self.height, 3)));
+        self
+    }
+
+    pub fn density(&self) -> &Array<f32, Ix2> {
+        &self.density
+    }
+
+    pub fn max_density(&self) -> f32 {
+        self.density.fold(0.0f32, |maximum, &d| maximum.max(d))
+    }
+
+    pub fn clear(&mut self) {
+        self.density.fill(0.0);
+
+        if let Some(colors) = &mut self.colors {
+            colors.fill(0.0);
+        }
+    }
+
+    /// Adds a weighted pass through the pixel which contains the point. The color is only
+    /// recorded by accumulators with color channels, which record white if it's missing.
+    pub fn add_point(&mut self, point: &Point2, weight: f32, color: Option<Rgb8>) {
+        let (x, y) = (
+            (point.x - self.canvas.left()) / self.canvas.w() * self.width as f32,
+            (point.y - self.canvas.bottom()) / self.canvas.h() * self.height as f32,
+        );
+
+        self.add_pixel(x, y, weight, color);
+    }
+
+    /// Adds a weighted pass through every pixel which the segment between two points crosses,
+    /// except for the start, so that the segments of a path count their shared points once.
+    pub fn add_segment(&mut self, start: &Point2, end: &Point2, weight: f32, color: Option<Rgb8>) {
+        let to_pixels = |point: &Point2| {
+            (
+                (point.x - self.canvas.left()) / self.canvas.w() * self.width as f32,
+                (point.y - self.canvas.bottom()) / self.canvas.h() * self.height as f32,
+            )
+        };
+
+        let ((x0, y0), (x1, y1)) = (to_pixels(start), to_pixels(end));
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as usize;
+
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+
+            self.add_pixel(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t, weight, color);
+        }
+    }
+
+    /// Tone maps the densities and colors them into an image, whose top row corresponds to
+    /// the top of the canvas.
+    pub fn render(&self, tone_mapping: &ToneMapping, coloring: &Coloring) -> RgbImage {
+        let max_density = self.max_density();
+
+        RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let (column, row) = (x as usize, self.height - 1 - y as usize);
+            let density = self.density[[column, row]];
+            let intensity = tone_mapping.apply(density, max_density);
+
+            let color = match coloring {
+                Coloring::Palette(gradient) => gradient.at(intensity),
+                Coloring::Channels { background } => {
+                    let average = |channel: usize| match (&self.colors, density > 0.0) {
+                        (Some(colors), true) => colors[[column, row, channel]] / density,
+                        _ => 255.0,
+                    };
+
+                    let blend = |background: u8, channel: usize| {
+                        (background as f32 + (average(channel) - background as f32) * intensity)
+                            .round()
+                            .clamp(0.0, 255.0) as u8
+                    };
+
+                    rgb8(
+                        blend(background.red, 0),
+                        blend(background.green, 1),
+                        blend(background.blue, 2),
+                    )
+                }
+            };
+
+            Rgb([color.red, color.green, color.blue])
+        })
+    }
+
+    fn add_pixel(&mut self, x: f32, y: f32, weight: f32, color: Option<Rgb8>) {
+        if x < 0.0 || y < 0.0 {
+            return;
+        }
+
+        let (column, row) = (x as usize, y as usize);
+
+        if column >= self.width || row >= self.height {
+            return;
+        }
+
+        self.density[[column, row]] += weight;
+
+        if let Some(colors) = &mut self.colors {
+            let color = color.unwrap_or_else(|| rgb8(255, 255, 255));
+
+            for (channel, value) in [color.red, color.green, color.blue].iter().enumerate() {
+                colors[[column, row, channel]] += *value as f32 * weight;
+            }
+        }
+    }
+}
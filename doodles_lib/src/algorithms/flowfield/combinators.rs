@@ -0,0 +1,271 @@
+//! Combinators which build new vector fields out of existing ones.
+//!
+//! Every combinator wraps one or more [`VectorField`]s and is a vector field itself, so
+//! combinators can be nested freely. They are usually constructed through the provided methods
+//! of [`VectorField`], e.g. a noise flowfield with a swirl around the centre, masked to a circle,
+//! is `(&flowfield).sum(Swirl::new(center)).masked(Uniform::zero(), circular_mask(..))`.
+//!
+//! A combinator of several fields treats a field which returns None at a point as a zero
+//! vector there, and returns None itself only when all of its fields do.
+use super::VectorField;
+use nannou::geom::{Point2, Vector2};
+
+/// Combines two optional vectors, treating a missing one as the zero vector.
+fn combine<F>(a: Option<Vector2>, b: Option<Vector2>, f: F) -> Option<Vector2>
+where
+    F: Fn(Vector2, Vector2) -> Vector2,
+{
+    match (a, b) {
+        (None, None) => None,
+        (a, b) => Some(f(
+            a.unwrap_or_else(Vector2::zero),
+            b.unwrap_or_else(Vector2::zero),
+        )),
+    }
+}
+
+/// A field which has the same vector everywhere.
+pub struct Uniform {
+    vector: Vector2,
+}
+
+impl Uniform {
+    pub fn new(vector: Vector2) -> Self {
+        Self { vector }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(Vector2::zero())
+    }
+}
+
+impl VectorField for Uniform {
+    fn sample(&self, _point: &Point2) -> Option<Vector2> {
+        Some(self.vector)
+    }
+}
+
+/// A field of unit vectors which circle counter-clockwise around a centre.
+pub struct Swirl {
+    center: Point2,
+}
+
+impl Swirl {
+    pub fn new(center: Point2) -> Self {
+        Self { center }
+    }
+}
+
+impl VectorField for Swirl {
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        let offset = *point - self.center;
+
+        match offset.x == 0.0 && offset.y == 0.0 {
+            true => Some(Vector2::zero()),
+            false => Some(Vector2::new(-offset.y, offset.x).normalize()),
+        }
+    }
+}
+
+/// A field defined by a function of the point.
+pub struct FromFn<F>
+where
+    F: Fn(&Point2) -> Option<Vector2>,
+{
+    f: F,
+}
+
+impl<F> FromFn<F>
+where
+    F: Fn(&Point2) -> Option<Vector2>,
+{
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<F> VectorField for FromFn<F>
+where
+    F: Fn(&Point2) -> Option<Vector2>,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        (self.f)(point)
+    }
+}
+
+/// The sum of two fields.
+pub struct Sum<A, B> {
+    pub(super) a: A,
+    pub(super) b: B,
+}
+
+impl<A, B> VectorField for Sum<A, B>
+where
+    A: VectorField,
+    B: VectorField,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        combine(self.a.sample(point), self.b.sample(point), |a, b| a + b)
+    }
+}
+
+/// A weighted blend of two fields, a weight of 0 yields the first field and 1 the second.
+pub struct Blend<A, B> {
+    pub(super) a: A,
+    pub(super) b: B,
+    pub(super) weight: f32,
+}
+
+impl<A, B> VectorField for Blend<A, B>
+where
+    A: VectorField,
+    B: VectorField,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        combine(self.a.sample(point), self.b.sample(point), |a, b| {
+            a * (1.0 - self.weight) + b * self.weight
+        })
+    }
+}
+
+/// A blend of two fields whose weight is given by a mask function of the point.
+///
+/// A mask value of 1 yields the first field and 0 the second one.
+pub struct Masked<A, B, M>
+where
+    M: Fn(&Point2) -> f32,
+{
+    pub(super) a: A,
+    pub(super) b: B,
+    pub(super) mask: M,
+}
+
+impl<A, B, M> VectorField for Masked<A, B, M>
+where
+    A: VectorField,
+    B: VectorField,
+    M: Fn(&Point2) -> f32,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        let weight = (self.mask)(point);
+
+        combine(self.a.sample(point), self.b.sample(point), |a, b| {
+            a * weight + b * (1.0 - weight)
+        })
+    }
+}
+
+/// Returns a mask which is 1 inside of a circle and falls off linearly to 0 over the feather
+/// distance outside of it.
+pub fn circular_mask(center: Point2, radius: f32, feather: f32) -> impl Fn(&Point2) -> f32 {
+    move |point| {
+        let distance = (*point - center).magnitude();
+
+        match feather > 0.0 {
+            true => (1.0 - (distance - radius) / feather).clamp(0.0, 1.0),
+            false => (distance <= radius) as u8 as f32,
+        }
+    }
+}
+
+/// A field whose vectors are multiplied by a factor.
+pub struct Scale<F> {
+    pub(super) field: F,
+    pub(super) factor: f32,
+}
+
+impl<F> VectorField for Scale<F>
+where
+    F: VectorField,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        self.field.sample(point).map(|v| v * self.factor)
+    }
+}
+
+/// A field whose vectors are rotated counter-clockwise by an angle, in radians.
+pub struct Rotate<F> {
+    pub(super) field: F,
+    pub(super) angle: f32,
+}
+
+impl<F> VectorField for Rotate<F>
+where
+    F: VectorField,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        self.field.sample(point).map(|v| v.rotate(self.angle))
+    }
+}
+
+/// A field whose vectors are scaled to unit length, zero vectors are left as they are.
+pub struct Normalize<F> {
+    pub(super) field: F,
+}
+
+impl<F> VectorField for Normalize<F>
+where
+    F: VectorField,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        self.field
+            .sample(point)
+            .map(|v| match v.x == 0.0 && v.y == 0.0 {
+                true => v,
+                false => v.normalize(),
+            })
+    }
+}
+
+/// A field whose domain is moved by an offset.
+pub struct Translate<F> {
+    pub(super) field: F,
+    pub(super) offset: Vector2,
+}
+
+impl<F> VectorField for Translate<F>
+where
+    F: VectorField,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        self.field.sample(&(*point - self.offset))
+    }
+}
+
+/// A field whose domain is rotated counter-clockwise by an angle, in radians, around a centre.
+///
+/// The vectors are rotated along with the domain, so that the field's structure is preserved.
+pub struct RotateDomain<F> {
+    pub(super) field: F,
+    pub(super) center: Point2,
+    pub(super) angle: f32,
+}
+
+impl<F> VectorField for RotateDomain<F>
+where
+    F: VectorField,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        let source = self.center + (*point - self.center).rotate(-self.angle);
+
+        self.field.sample(&source).map(|v| v.rotate(self.angle))
+    }
+}
+
+/// A field whose domain is scaled by a factor around a centre.
+pub struct ScaleDomain<F> {
+    pub(super) field: F,
+    pub(super) center: Point2,
+    pub(super) factor: f32,
+}
+
+impl<F> VectorField for ScaleDomain<F>
+where
+    F: VectorField,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        let source = self.center + (*point - self.center) / self.factor;
+
+        self.field.sample(&source)
+    }
+}
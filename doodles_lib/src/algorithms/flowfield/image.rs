@@ -0,0 +1,250 @@
+//! Provides a flowfield [`Source`] which derives its vectors from a picture.
+//!
+//! The picture is converted to luminance and its gradient is calculated with a Sobel operator.
+//! Depending on the selected [`ImageFlowMode`] the gradient itself, its perpendicular
+//! (the isophote direction) or a smoothed edge tangent flow is used. The per pixel vectors are
+//! then resampled to the resolution of the flowfield which uses the source.
+use super::Source;
+use nannou::{
+    geom::Vector2,
+    image::{self, DynamicImage, ImageResult},
+};
+use ndarray::{Array, Ix2};
+use std::path::Path;
+
+/// Enumeration of the ways in which vectors can be derived from a picture.
+#[derive(Debug, Copy, Clone)]
+pub enum ImageFlowMode {
+    /// Vectors point in the direction in which the luminance increases the most.
+    Gradient,
+    /// Vectors are perpendicular to the gradient, following lines of equal luminance.
+    Isophote,
+    /// Vectors follow the edges of the picture, the isophote directions are smoothed
+    /// `iterations` times over a neighbourhood of `radius` pixels, as described by Kang et al.
+    /// in "Coherent Line Drawing".
+    EdgeTangent { radius: u32, iterations: u32 },
+}
+
+/// A [`Source`] which fills a flowfield with vectors derived from a picture.
+///
+/// The vectors are calculated once, when the source is constructed, and so are their averages
+/// over the cells of the flowfield's grid, the first time the grid is filled. As the picture
+/// doesn't change, every following update copies the stored averages.
+pub struct ImageSource {
+    mode: ImageFlowMode,
+    vectors: Array<Vector2, Ix2>,
+    grid: Option<(u32, u32, Vec<Vector2>)>,
+}
+
+impl ImageSource {
+    /// Constructs a new instance of [`ImageSource`] from an already loaded picture.
+    pub fn new(picture: &DynamicImage, mode: ImageFlowMode) -> Self {
+        let luminance = calculate_luminance(picture);
+        let (gradient, magnitude) = calculate_gradient(&luminance);
+
+        let vectors = match mode {
+            ImageFlowMode::Gradient => gradient,
+            ImageFlowMode::Isophote => gradient.mapv(perpendicular),
+            ImageFlowMode::EdgeTangent { radius, iterations } => {
+                calculate_edge_tangent_flow(gradient, &magnitude, radius, iterations)
+            }
+        };
+
+        Self {
+            mode,
+            vectors,
+            grid: None,
+        }
+    }
+
+    /// Loads a picture (PNG, JPEG, etc.) from disk and constructs a new [`ImageSource`] from it.
+    pub fn open<P>(path: P, mode: ImageFlowMode) -> ImageResult<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::new(&image::open(path)?, mode))
+    }
+
+    /// Averages the vectors of the block of pixels covered by a single cell of a flowfield.
+    ///
+    /// Gradient vectors are simply summed, while isophote and edge tangent vectors, which only
+    /// denote an orientation, are flipped to agree with the first non-zero vector of the block
+    /// so that opposite vectors do not cancel each other out.
+    fn average_block(&self, x_start: usize, x_end: usize, y_start: usize, y_end: usize) -> Vector2 {
+        let block = self
+            .vectors
+            .slice(ndarray::s![x_start..x_end, y_start..y_end]);
+
+        let reference = block.iter().find(|v| !is_zero(v)).copied();
+
+        let sum = block
+            .iter()
+            .fold(Vector2::zero(), |sum, &v| match (self.mode, reference) {
+                (ImageFlowMode::Gradient, _) | (_, None) => sum + v,
+                (_, Some(r)) if v.dot(r) < 0.0 => sum - v,
+                _ => sum + v,
+            });
+
+        match is_zero(&sum) {
+            true => Vector2::zero(),
+            false => sum.normalize(),
+        }
+    }
+
+    /// Stretches the picture over a grid of the given size, averaging the vectors of each cell.
+    fn average_grid(&self, rows: u32, columns: u32) -> Vec<Vector2> {
+        let shape = self.vectors.shape();
+        let (width, height) = (shape[0], shape[1]);
+
+        let mut vectors = Vec::with_capacity((rows * columns) as usize);
+
+        // Both the grid's and the picture's rows start at the top.
+        for row in 0..rows {
+            let y_start = height * row as usize / rows as usize;
+            let y_end = (height * (row + 1) as usize / rows as usize).max(y_start + 1);
+
+            for column in 0..columns {
+                let x_start = width * column as usize / columns as usize;
+                let x_end = (width * (column + 1) as usize / columns as usize).max(x_start + 1);
+
+                vectors.push(self.average_block(
+                    x_start,
+                    x_end.min(width),
+                    y_start,
+                    y_end.min(height),
+                ));
+            }
+        }
+
+        vectors
+    }
+}
+
+impl Source for ImageSource {
+    fn fill(&mut self, vectors: &mut [Vector2], rows: u32, columns: u32) {
+        let is_stored = matches!(&self.grid, Some((r, c, _)) if *r == rows && *c == columns);
+
+        if !is_stored {
+            self.grid = Some((rows, columns, self.average_grid(rows, columns)));
+        }
+
+        if let Some((_, _, averages)) = &self.grid {
+            vectors.copy_from_slice(averages);
+        }
+    }
+}
+
+fn is_zero(v: &Vector2) -> bool {
+    v.x == 0.0 && v.y == 0.0
+}
+
+fn perpendicular(v: Vector2) -> Vector2 {
+    Vector2::new(-v.y, v.x)
+}
+
+/// Converts a picture to an array of luminance values in the range [0..1],
+/// indexed by the x and y coordinates of each pixel.
+fn calculate_luminance(picture: &DynamicImage) -> Array<f32, Ix2> {
+    let luma = picture.to_luma8();
+    let (width, height) = luma.dimensions();
+
+    Array::from_shape_fn((width as usize, height as usize), |(x, y)| {
+        luma.get_pixel(x as u32, y as u32)[0] as f32 / 255.0
+    })
+}
+
+/// Calculates the gradient of the luminance with a Sobel operator.
+///
+/// Returns the unit gradient vectors, in screen orientation (y pointing up), and the gradient
+/// magnitudes normalized to the range [0..1].
+fn calculate_gradient(luminance: &Array<f32, Ix2>) -> (Array<Vector2, Ix2>, Array<f32, Ix2>) {
+    let shape = luminance.shape();
+    let (width, height) = (shape[0] as isize, shape[1] as isize);
+
+    let at = |x: isize, y: isize| {
+        luminance[[
+            x.max(0).min(width - 1) as usize,
+            y.max(0).min(height - 1) as usize,
+        ]]
+    };
+
+    let gradient = Array::from_shape_fn((width as usize, height as usize), |(x, y)| {
+        let (x, y) = (x as isize, y as isize);
+
+        let gx = (at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1))
+            - (at(x - 1, y - 1) + 2.0 * at(x - 1, y) + at(x - 1, y + 1));
+        let gy = (at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1))
+            - (at(x - 1, y - 1) + 2.0 * at(x, y - 1) + at(x + 1, y - 1));
+
+        // The picture's y axis points down, while the screen's points up.
+        Vector2::new(gx, -gy)
+    });
+
+    let magnitude = gradient.mapv(|v| v.magnitude());
+    let max_magnitude = magnitude.fold(0.0f32, |max, &m| max.max(m));
+
+    let gradient = gradient.mapv(|v| match is_zero(&v) {
+        true => v,
+        false => v.normalize(),
+    });
+
+    let magnitude = match max_magnitude > 0.0 {
+        true => magnitude / max_magnitude,
+        false => magnitude,
+    };
+
+    (gradient, magnitude)
+}
+
+/// Calculates the edge tangent flow of a picture.
+///
+/// The flow starts out as the isophote directions and is iteratively smoothed. Each new vector
+/// is a weighted sum of its neighbours, where neighbours with a larger gradient magnitude and
+/// a similar direction contribute more.
+fn calculate_edge_tangent_flow(
+    gradient: Array<Vector2, Ix2>,
+    magnitude: &Array<f32, Ix2>,
+    radius: u32,
+    iterations: u32,
+) -> Array<Vector2, Ix2> {
+    let shape = gradient.shape();
+    let (width, height) = (shape[0] as isize, shape[1] as isize);
+    let radius = radius as isize;
+
+    let mut flow = gradient.mapv(perpendicular);
+
+    for _ in 0..iterations {
+        flow = Array::from_shape_fn((width as usize, height as usize), |(x, y)| {
+            let tangent = flow[[x, y]];
+            let (x, y) = (x as isize, y as isize);
+
+            let mut sum = Vector2::zero();
+
+            for ny in (y - radius).max(0)..=(y + radius).min(height - 1) {
+                for nx in (x - radius).max(0)..=(x + radius).min(width - 1) {
+                    if (nx - x).pow(2) + (ny - y).pow(2) > radius.pow(2) {
+                        continue;
+                    }
+
+                    let neighbour = flow[[nx as usize, ny as usize]];
+                    let alignment = tangent.dot(neighbour);
+
+                    let magnitude_weight = 0.5
+                        * (1.0
+                            + (magnitude[[nx as usize, ny as usize]]
+                                - magnitude[[x as usize, y as usize]])
+                            .tanh());
+
+                    sum += neighbour * (alignment * magnitude_weight);
+                }
+            }
+
+            match is_zero(&sum) {
+                true => tangent,
+                false => sum.normalize(),
+            }
+        });
+    }
+
+    flow
+}
@@ -0,0 +1,379 @@
+//! Provides a grid based flowfield (vector field) which can be used to steer particles.
+//!
+//! The vectors of a [`Flowfield`] are produced by a [`Source`]. This module provides
+//! a noise based source, [`Noise`], while the [`image`] module provides a source which derives
+//! the vectors from the structure of a picture.
+//!
+//! Anything which can be sampled for a vector at a point of the canvas should implement
+//! [`VectorField`], so that particles can be steered by it regardless of where it comes from,
+//! and so that it can be combined with other fields through the [`combinators`] or steered
+//! around [`obstacles`].
+use nannou::{
+    color::rgb8,
+    geom::{pt2, vec3, Point2, Rect, Vector2},
+    math::map_range,
+    noise::{NoiseFn, Seedable},
+    prelude::TAU_F64,
+    Draw,
+};
+
+pub mod combinators;
+pub mod image;
+pub mod obstacles;
+pub mod snapshot;
+
+use combinators::{
+    Blend, Masked, Normalize, Rotate, RotateDomain, Scale, ScaleDomain, Sum, Translate,
+};
+use snapshot::Snapshot;
+
+/// The Source trait describes where the vectors of a [`Flowfield`] come from.
+///
+/// Every time a flowfield is updated it hands its grid of vectors to its source, which must
+/// fill it. The grid is stored row by row, starting from the top left cell of the canvas, which
+/// is the order in which [`Flowfield::display`] draws it and [`Flowfield::get_vector_at`]
+/// samples it.
+pub trait Source {
+    fn fill(&mut self, vectors: &mut [Vector2], rows: u32, columns: u32);
+
+    /// Returns the parameters of a noise based source, which are stored in a flowfield's
+    /// [`snapshot::Snapshot`]. Sources which aren't noise based have none.
+    fn noise_parameters(&self) -> Option<NoiseParameters> {
+        None
+    }
+}
+
+/// The parameters of a [`Noise`] source, which are needed to recreate it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NoiseParameters {
+    pub seed: u32,
+    pub z_offset: f64,
+    pub xy_increment: f64,
+    pub z_increment: f64,
+}
+
+/// The VectorField trait provides a common way of sampling vector fields.
+///
+/// Returns the vector at the given point of the canvas or None if the point is outside of
+/// the field. The provided methods wrap a field in one of the [`combinators`], producing a new
+/// vector field.
+pub trait VectorField {
+    fn sample(&self, point: &Point2) -> Option<Vector2>;
+
+    /// Draws the field's vectors on a grid of cells, each one resolution wide, starting from the
+    /// top left of the canvas just like [`Flowfield::display`]. Every vector is sampled at the
+    /// center of its cell.
+    fn display_grid(&self, draw: &Draw, canvas: &Rect, resolution: u32) {
+        let rows = (canvas.h() / resolution as f32).floor() as u32 + 1;
+        let columns = (canvas.w() / resolution as f32).floor() as u32 + 1;
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let x = (canvas.left() + (resolution as f32 / 2.0)) + (resolution * column) as f32;
+                let y = (canvas.top() - (resolution as f32 / 2.0)) - (resolution * row) as f32;
+
+                if let Some(vector) = self.sample(&pt2(x, y)) {
+                    draw.translate(vec3(x, y, 0.0))
+                        .line()
+                        .rotate(vector.angle())
+                        .color(rgb8(0, 0, 0))
+                        .weight(1.0)
+                        .points(pt2(0.0, 0.0), pt2(resolution as f32 / 2.0, 0.0));
+                }
+            }
+        }
+    }
+
+    fn sum<F>(self, other: F) -> Sum<Self, F>
+    where
+        Self: Sized,
+    {
+        Sum { a: self, b: other }
+    }
+
+    fn blend<F>(self, other: F, weight: f32) -> Blend<Self, F>
+    where
+        Self: Sized,
+    {
+        Blend {
+            a: self,
+            b: other,
+            weight,
+        }
+    }
+
+    fn masked<F, M>(self, other: F, mask: M) -> Masked<Self, F, M>
+    where
+        Self: Sized,
+        M: Fn(&Point2) -> f32,
+    {
+        Masked {
+            a: self,
+            b: other,
+            mask,
+        }
+    }
+
+    fn scale(self, factor: f32) -> Scale<Self>
+    where
+        Self: Sized,
+    {
+        Scale {
+            field: self,
+            factor,
+        }
+    }
+
+    fn rotate(self, angle: f32) -> Rotate<Self>
+    where
+        Self: Sized,
+    {
+        Rotate { field: self, angle }
+    }
+
+    fn normalize(self) -> Normalize<Self>
+    where
+        Self: Sized,
+    {
+        Normalize { field: self }
+    }
+
+    fn translate(self, offset: Vector2) -> Translate<Self>
+    where
+        Self: Sized,
+    {
+        Translate {
+            field: self,
+            offset,
+        }
+    }
+
+    fn rotate_domain(self, center: Point2, angle: f32) -> RotateDomain<Self>
+    where
+        Self: Sized,
+    {
+        RotateDomain {
+            field: self,
+            center,
+            angle,
+        }
+    }
+
+    fn scale_domain(self, center: Point2, factor: f32) -> ScaleDomain<Self>
+    where
+        Self: Sized,
+    {
+        ScaleDomain {
+            field: self,
+            center,
+            factor,
+        }
+    }
+}
+
+impl<T> VectorField for &T
+where
+    T: VectorField + ?Sized,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        (**self).sample(point)
+    }
+}
+
+pub struct Noise<T>
+where
+    T: Seedable + NoiseFn<nannou::noise::Point3<f64>>,
+{
+    generator: T,
+    z_offset: f64,
+    xy_increment: f64,
+    z_increment: f64,
+}
+
+impl<T> Noise<T>
+where
+    T: Seedable + NoiseFn<nannou::noise::Point3<f64>>,
+{
+    pub fn new(generator: T, z_offset: f64, xy_increment: f64, z_increment: f64) -> Self {
+        Self {
+            generator,
+            z_offset,
+            xy_increment,
+            z_increment,
+        }
+    }
+
+    /// Constructs a new instance of [`Noise`] which continues from previously stored parameters.
+    pub fn from_parameters(generator: T, parameters: &NoiseParameters) -> Self {
+        Self::new(
+            generator.set_seed(parameters.seed),
+            parameters.z_offset,
+            parameters.xy_increment,
+            parameters.z_increment,
+        )
+    }
+}
+
+impl<T> Source for Noise<T>
+where
+    T: Seedable + NoiseFn<nannou::noise::Point3<f64>>,
+{
+    fn fill(&mut self, vectors: &mut [Vector2], rows: u32, columns: u32) {
+        let mut x_offset = 0.0;
+
+        for row in 0..rows {
+            let mut y_offset = 0.0;
+
+            for column in 0..columns {
+                vectors[(column + row * columns) as usize] = Vector2::from_angle(
+                    (self.generator.get([x_offset, y_offset, self.z_offset]) * TAU_F64) as f32,
+                );
+                y_offset += self.xy_increment
+            }
+
+            x_offset += self.xy_increment;
+        }
+
+        self.z_offset += self.z_increment;
+    }
+
+    fn noise_parameters(&self) -> Option<NoiseParameters> {
+        Some(NoiseParameters {
+            seed: self.generator.seed(),
+            z_offset: self.z_offset,
+            xy_increment: self.xy_increment,
+            z_increment: self.z_increment,
+        })
+    }
+}
+
+pub struct Flowfield<S>
+where
+    S: Source,
+{
+    rows: u32,
+    columns: u32,
+    resolution: u32,
+    pub canvas: Rect,
+    vectors: Vec<Vector2>,
+    source: S,
+}
+
+impl<S> Flowfield<S>
+where
+    S: Source,
+{
+    pub fn new(canvas: Rect, source: S, resolution: u32) -> Self {
+        let rows = (canvas.h() / resolution as f32).floor() as u32 + 1;
+        let columns = (canvas.w() / resolution as f32).floor() as u32 + 1;
+
+        let vectors = vec![Vector2::zero(); (rows * columns) as usize];
+
+        Self {
+            rows,
+            columns,
+            resolution,
+            canvas,
+            vectors,
+            source,
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.source.fill(&mut self.vectors, self.rows, self.columns);
+    }
+
+    pub fn display(&self, draw: &Draw) {
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let x = (self.canvas.left() + (self.resolution as f32 / 2.0))
+                    + (self.resolution * column) as f32;
+                let y = (self.canvas.top() - (self.resolution as f32 / 2.0))
+                    - (self.resolution * row) as f32;
+
+                draw.translate(vec3(x, y, 0.0))
+                    .line()
+                    .rotate(self.vectors[(column + row * self.columns) as usize].angle())
+                    .color(rgb8(0, 0, 0))
+                    .weight(1.0)
+                    .points(pt2(0.0, 0.0), pt2(self.resolution as f32 / 2.0, 0.0));
+            }
+        }
+    }
+
+    pub fn get_vector_at(&self, point: &Point2) -> Option<&Vector2> {
+        get_grid_vector(
+            &self.vectors,
+            &self.canvas,
+            self.resolution,
+            self.rows,
+            self.columns,
+            point,
+        )
+    }
+
+    /// Captures the current state of the flowfield, see [`snapshot`] for how it can be stored.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            canvas: self.canvas,
+            resolution: self.resolution,
+            rows: self.rows,
+            columns: self.columns,
+            noise: self.source.noise_parameters(),
+            vectors: self.vectors.clone(),
+        }
+    }
+
+    /// Constructs a new flowfield whose vectors are restored from a snapshot.
+    ///
+    /// The source is used for all following updates, use [`Noise::from_parameters`]
+    /// with the snapshot's noise parameters to continue where a noise based flowfield stopped.
+    pub fn from_snapshot(snapshot: Snapshot, source: S) -> Self {
+        Self {
+            rows: snapshot.rows,
+            columns: snapshot.columns,
+            resolution: snapshot.resolution,
+            canvas: snapshot.canvas,
+            vectors: snapshot.vectors,
+            source,
+        }
+    }
+}
+
+/// Looks up the vector of the grid cell which contains the point,
+/// the grid is stored row by row, starting from the top left cell of the canvas.
+///
+/// Returns None if the point lies outside of the grid.
+fn get_grid_vector<'a>(
+    vectors: &'a [Vector2],
+    canvas: &Rect,
+    resolution: u32,
+    rows: u32,
+    columns: u32,
+    point: &Point2,
+) -> Option<&'a Vector2> {
+    let mapped_x = map_range(point.x, canvas.left(), canvas.right(), 0.0, canvas.w());
+    let mapped_y = map_range(point.y, canvas.top(), canvas.bottom(), 0.0, canvas.h());
+
+    // Negative values would saturate to the first cell, and NaNs fail both checks.
+    if !(mapped_x >= 0.0 && mapped_y >= 0.0) {
+        return None;
+    }
+
+    let column = (mapped_x / resolution as f32).floor() as u32;
+    let row = (mapped_y / resolution as f32).floor() as u32;
+
+    match column < columns && row < rows {
+        true => vectors.get((column + row * columns) as usize),
+        false => None,
+    }
+}
+
+impl<S> VectorField for Flowfield<S>
+where
+    S: Source,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        self.get_vector_at(point).copied()
+    }
+}
@@ -0,0 +1,304 @@
+//! Provides a vector field which steers the vectors of another field around obstacles.
+//!
+//! Obstacles are circles (e.g. the points of a poisson-disc sampling together with their radius)
+//! or polygons (e.g. tiles). Close to an obstacle the vectors of the wrapped field bend around it,
+//! either following the potential flow around a cylinder or simply turning away from it.
+//! In both cases no vector within the influence distance of an obstacle points into the closest
+//! one.
+use super::VectorField;
+use crate::{collections::SpatialHash, geometry::polygon};
+use nannou::geom::{Point2, Rect, Vector2};
+use std::io::{self, ErrorKind};
+
+/// Enumeration of the shapes which a flow can be steered around.
+#[derive(Debug, Clone)]
+pub enum Obstacle {
+    Circle {
+        center: Point2,
+        radius: f32,
+    },
+    /// A simple polygon with at least three vertices, use [`Obstacle::polygon`] to construct a
+    /// validated one.
+    Polygon(Vec<Point2>),
+}
+
+impl Obstacle {
+    /// Constructs a polygonal obstacle.
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if the polygon has fewer than three vertices.
+    pub fn polygon(vertices: Vec<Point2>) -> io::Result<Self> {
+        let obstacle = Obstacle::Polygon(vertices);
+        obstacle.validate()?;
+
+        Ok(obstacle)
+    }
+
+    /// Checks whether the obstacle has an outline, which polygons with fewer than three
+    /// vertices don't.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Obstacle::Circle { .. } => true,
+            Obstacle::Polygon(vertices) => vertices.len() >= 3,
+        }
+    }
+
+    /// Fails with [`ErrorKind::InvalidInput`] if the obstacle isn't valid, see
+    /// [`Obstacle::is_valid`].
+    pub fn validate(&self) -> io::Result<()> {
+        match self.is_valid() {
+            true => Ok(()),
+            false => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Polygonal obstacles need at least three vertices.",
+            )),
+        }
+    }
+
+    pub fn from_rect(rect: &Rect) -> Self {
+        Obstacle::Polygon(vec![
+            rect.bottom_left(),
+            rect.bottom_right(),
+            rect.top_right(),
+            rect.top_left(),
+        ])
+    }
+
+    /// Returns the signed distance between the point and the outline of the obstacle, which is
+    /// negative inside of the obstacle, together with the outward normal closest to the point.
+    pub fn distance_and_normal(&self, point: &Point2) -> (f32, Vector2) {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                let offset = *point - *center;
+                let distance = offset.magnitude();
+
+                match distance > 0.0 {
+                    true => (distance - radius, offset / distance),
+                    false => (-radius, Vector2::new(1.0, 0.0)),
+                }
+            }
+            Obstacle::Polygon(vertices) => {
+                let offset = *point - polygon::closest_point(vertices, point);
+                let distance = offset.magnitude();
+
+                let (distance, normal) = match polygon::contains(vertices, point) {
+                    true => (-distance, -offset),
+                    false => (distance, offset),
+                };
+
+                // On the outline the offset is zero or rounding noise, which has no meaningful
+                // direction, so the point gets the normal of its edge instead.
+                let bounds = polygon::bounds(vertices);
+
+                match distance.abs() <= bounds.w().max(bounds.h()) * 1e-5 {
+                    true => (distance, edge_normal(vertices, point)),
+                    false => (distance, normal.normalize()),
+                }
+            }
+        }
+    }
+
+    /// Returns the bounding box of the obstacle.
+    pub fn bounds(&self) -> Rect {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                Rect::from_x_y_w_h(center.x, center.y, radius * 2.0, radius * 2.0)
+            }
+            Obstacle::Polygon(vertices) => polygon::bounds(vertices),
+        }
+    }
+}
+
+/// Enumeration of the ways in which vectors are steered around obstacles.
+#[derive(Debug, Copy, Clone)]
+pub enum Avoidance {
+    /// A vector which points into an obstacle loses the part which points into it, and is
+    /// gradually sped up along the obstacle's outline as it gets closer to the obstacle, until
+    /// it has its original magnitude.
+    Deflection,
+    /// Around circles the vectors follow the potential flow around a cylinder, which has the
+    /// vector of the wrapped field as its free stream velocity. Polygons are deflected.
+    Potential,
+}
+
+/// A vector field which steers the vectors of another field around a set of obstacles.
+///
+/// Obstacles only affect the points closer to their outline than the influence distance,
+/// points inside of an obstacle get a vector which points out of it.
+pub struct ObstacleField<F> {
+    field: F,
+    obstacles: Vec<Obstacle>,
+    avoidance: Avoidance,
+    influence: f32,
+    spatial_hash: SpatialHash,
+}
+
+impl<F> ObstacleField<F>
+where
+    F: VectorField,
+{
+    /// Constructs a new instance of [`ObstacleField`].
+    ///
+    /// The obstacles are bucketed into a [`SpatialHash`], so that every sample only visits the
+    /// obstacles which are close to it.
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if a polygonal obstacle has fewer than three
+    /// vertices, see [`Obstacle::polygon`].
+    pub fn new(
+        field: F,
+        obstacles: Vec<Obstacle>,
+        avoidance: Avoidance,
+        influence: f32,
+    ) -> io::Result<Self> {
+        for obstacle in &obstacles {
+            obstacle.validate()?;
+        }
+
+        let cell_size = obstacles
+            .iter()
+            .map(|o| o.bounds().w().max(o.bounds().h()))
+            .fold(0.0f32, f32::max)
+            .max(1.0)
+            + influence * 2.0;
+
+        let mut spatial_hash = SpatialHash::new(cell_size);
+
+        // Every obstacle is found in all cells within the influence distance of its bounds.
+        for (index, obstacle) in obstacles.iter().enumerate() {
+            spatial_hash.insert_rect(index, &obstacle.bounds().pad(-influence));
+        }
+
+        Ok(Self {
+            field,
+            obstacles,
+            avoidance,
+            influence,
+            spatial_hash,
+        })
+    }
+
+    pub fn obstacles(&self) -> &[Obstacle] {
+        &self.obstacles
+    }
+}
+
+impl<F> VectorField for ObstacleField<F>
+where
+    F: VectorField,
+{
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        let mut vector = self.field.sample(point)?;
+        let magnitude = vector.magnitude();
+
+        // A query without a radius visits the single cell which contains the point, which holds
+        // every obstacle at most once.
+        let mut nearby: Vec<(f32, Vector2, &Obstacle)> = self
+            .spatial_hash
+            .query(point, 0.0)
+            .map(|index| {
+                let obstacle = &self.obstacles[index];
+                let (distance, normal) = obstacle.distance_and_normal(point);
+
+                (distance, normal, obstacle)
+            })
+            .filter(|(distance, _, _)| *distance < self.influence)
+            .collect();
+
+        // The closest obstacle is handled last, so that the result never points into it.
+        nearby.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        for (distance, normal, obstacle) in nearby {
+            if distance <= 0.0 {
+                return Some(normal * magnitude);
+            }
+
+            let weight = falloff(distance / self.influence);
+
+            if let (Avoidance::Potential, Obstacle::Circle { center, radius }) =
+                (self.avoidance, obstacle)
+            {
+                vector += potential_disturbance(&vector, &(*point - *center), *radius) * weight;
+            }
+
+            let inward = vector.dot(normal);
+
+            // The inward part is always removed, the weight only restores the speed which the
+            // vector lost, so that it flows along the outline close to the obstacle.
+            if inward < 0.0 {
+                let speed = vector.magnitude();
+                let tangent = vector - normal * inward;
+                let tangent_speed = tangent.magnitude();
+
+                // Head-on the tangent is only rounding noise, so any direction along the
+                // outline will do.
+                let direction = match tangent_speed > speed * 1e-3 {
+                    true => tangent / tangent_speed,
+                    false => Vector2::new(-normal.y, normal.x),
+                };
+
+                vector = direction * (tangent_speed + (speed - tangent_speed) * weight);
+            }
+        }
+
+        Some(vector)
+    }
+}
+
+/// Returns the outward normal of the polygon's edge which is the closest to the point.
+fn edge_normal(vertices: &[Point2], point: &Point2) -> Vector2 {
+    let edges = (0..vertices.len()).map(|i| (vertices[i], vertices[(i + 1) % vertices.len()]));
+
+    let distance = |(a, b): &(Point2, Point2)| {
+        (polygon::closest_point_on_segment(a, b, point) - *point).magnitude2()
+    };
+
+    let normal = edges
+        .min_by(|x, y| {
+            distance(x)
+                .partial_cmp(&distance(y))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|(a, b)| Vector2::new(b.y - a.y, a.x - b.x))
+        .unwrap_or_else(Vector2::zero);
+
+    // The right hand side of an edge is outside of a counter-clockwise polygon.
+    let normal = match polygon::signed_area(vertices) < 0.0 {
+        true => -normal,
+        false => normal,
+    };
+
+    match normal.x == 0.0 && normal.y == 0.0 {
+        true => Vector2::new(1.0, 0.0),
+        false => normal.normalize(),
+    }
+}
+
+/// A smooth step from 1, on the outline of an obstacle, to 0 at the influence distance.
+fn falloff(t: f32) -> f32 {
+    let t = 1.0 - t.clamp(0.0, 1.0);
+
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Returns the disturbance which a cylinder adds to a uniform flow.
+///
+/// By the circle theorem, a free stream velocity V around a cylinder of radius R has the complex
+/// velocity V - conj(V) R² / conj(z)², where z is the offset from the cylinder's centre.
+fn potential_disturbance(velocity: &Vector2, offset: &Vector2, radius: f32) -> Vector2 {
+    // conj(z)² = (x - iy)² = (x² - y²) - 2xyi
+    let (zr, zi) = (
+        offset.x * offset.x - offset.y * offset.y,
+        -2.0 * offset.x * offset.y,
+    );
+    let denominator = zr * zr + zi * zi;
+
+    if denominator == 0.0 {
+        return Vector2::zero();
+    }
+
+    // conj(V) / conj(z)², with conj(V) = vx - i vy
+    let (vr, vi) = (velocity.x, -velocity.y);
+    let quotient_r = (vr * zr + vi * zi) / denominator;
+    let quotient_i = (vi * zr - vr * zi) / denominator;
+
+    Vector2::new(-quotient_r, -quotient_i) * (radius * radius)
+}
@@ -0,0 +1,391 @@
+//! Provides saving and loading of flowfield states, as single snapshots or as time series.
+//!
+//! Both kinds of files are binary and all of their values are little-endian.
+//!
+//! A snapshot file (`.flow`) is made of a header followed by a single frame:
+//!
+//! | Field       | Type       | Description                                            |
+//! |-------------|------------|--------------------------------------------------------|
+//! | magic       | `[u8; 4]`  | `FLOW` for a snapshot, `FLWS` for a time series        |
+//! | version     | `u16`      | currently `1`                                          |
+//! | canvas      | `[f32; 4]` | left, bottom, right and top edge of the canvas         |
+//! | resolution  | `u32`      | width and height of a single cell                      |
+//! | rows        | `u32`      | number of rows of the grid                             |
+//! | columns     | `u32`      | number of columns of the grid                          |
+//!
+//! A time series file (`.flws`) has the same header followed by any number of frames, one for
+//! each recorded update, until the end of the file. Each frame is made of:
+//!
+//! | Field        | Type                     | Description                                   |
+//! |--------------|--------------------------|-----------------------------------------------|
+//! | has noise    | `u8`                     | `1` if noise parameters follow, `0` otherwise |
+//! | seed         | `u32`                    | noise parameters, only present if has noise   |
+//! | z offset     | `f64`                    |                                               |
+//! | xy increment | `f64`                    |                                               |
+//! | z increment  | `f64`                    |                                               |
+//! | vectors      | `[[f32; 2]; rows * cols]`| x and y of each vector, row by row, starting  |
+//! |              |                          | from the top left cell of the canvas          |
+use super::{get_grid_vector, NoiseParameters, VectorField};
+use nannou::geom::{pt2, Point2, Rect, Vector2};
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+    path::Path,
+};
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"FLOW";
+const SERIES_MAGIC: &[u8; 4] = b"FLWS";
+const VERSION: u16 = 1;
+
+/// The state of a flowfield at a single point in time.
+///
+/// A snapshot is a vector field itself, so a loaded snapshot can be sampled and displayed
+/// without a source, or compared to another one with [`Snapshot::difference`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub(super) canvas: Rect,
+    pub(super) resolution: u32,
+    pub(super) rows: u32,
+    pub(super) columns: u32,
+    pub(super) noise: Option<NoiseParameters>,
+    pub(super) vectors: Vec<Vector2>,
+}
+
+impl Snapshot {
+    pub fn canvas(&self) -> &Rect {
+        &self.canvas
+    }
+
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    pub fn noise_parameters(&self) -> Option<&NoiseParameters> {
+        self.noise.as_ref()
+    }
+
+    pub fn vectors(&self) -> &[Vector2] {
+        &self.vectors
+    }
+
+    /// Writes the snapshot to a file, in the format described in the module documentation.
+    pub fn save<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        write_header(&mut writer, SNAPSHOT_MAGIC, self)?;
+        write_frame(&mut writer, self)?;
+
+        writer.flush()
+    }
+
+    /// Reads a snapshot from a file, in the format described in the module documentation.
+    pub fn load<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let header = read_header(&mut reader, SNAPSHOT_MAGIC)?;
+
+        read_frame(&mut reader, &header)?
+            .ok_or_else(|| io::Error::new(ErrorKind::UnexpectedEof, "The snapshot has no frame."))
+    }
+
+    /// Calculates the largest distance between the vectors of two snapshots.
+    ///
+    /// Returns None if the snapshots have grids of a different size, which cannot be compared.
+    pub fn difference(&self, other: &Snapshot) -> Option<f32> {
+        match self.rows == other.rows && self.columns == other.columns {
+            true => Some(
+                self.vectors
+                    .iter()
+                    .zip(&other.vectors)
+                    .map(|(a, b)| (*a - *b).magnitude())
+                    .fold(0.0, f32::max),
+            ),
+            false => None,
+        }
+    }
+}
+
+impl VectorField for Snapshot {
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        get_grid_vector(
+            &self.vectors,
+            &self.canvas,
+            self.resolution,
+            self.rows,
+            self.columns,
+            point,
+        )
+        .copied()
+    }
+}
+
+/// Records successive states of a flowfield into a time series file.
+pub struct Recorder<W>
+where
+    W: Write,
+{
+    writer: W,
+    header: Snapshot,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Creates a time series file and records the first snapshot, the grid of all following
+    /// snapshots must match the grid of the first one.
+    pub fn create<P>(path: P, first: &Snapshot) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::new(BufWriter::new(File::create(path)?), first)
+    }
+}
+
+impl<W> Recorder<W>
+where
+    W: Write,
+{
+    /// Constructs a new instance of [`Recorder`], writes the time series header
+    /// and records the first snapshot.
+    pub fn new(mut writer: W, first: &Snapshot) -> io::Result<Self> {
+        write_header(&mut writer, SERIES_MAGIC, first)?;
+        write_frame(&mut writer, first)?;
+
+        let header = Snapshot {
+            vectors: vec![],
+            noise: None,
+            ..first.clone()
+        };
+
+        Ok(Self { writer, header })
+    }
+
+    /// Appends a snapshot to the time series.
+    pub fn record(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        if snapshot.rows != self.header.rows || snapshot.columns != self.header.columns {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "The snapshot's grid does not match the recorded grid.",
+            ));
+        }
+
+        write_frame(&mut self.writer, snapshot)
+    }
+
+    /// Flushes all recorded snapshots and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Replays a time series file frame by frame.
+///
+/// Replay is an iterator over the recorded snapshots, which stops at the end of the file
+/// or at the first frame which cannot be read.
+pub struct Replay<R>
+where
+    R: Read,
+{
+    reader: R,
+    header: Snapshot,
+}
+
+impl Replay<BufReader<File>> {
+    pub fn open<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R> Replay<R>
+where
+    R: Read,
+{
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let header = read_header(&mut reader, SERIES_MAGIC)?;
+
+        Ok(Self { reader, header })
+    }
+
+    /// Reads the next frame, returns None at the end of the time series.
+    pub fn next_frame(&mut self) -> io::Result<Option<Snapshot>> {
+        read_frame(&mut self.reader, &self.header)
+    }
+}
+
+impl<R> Iterator for Replay<R>
+where
+    R: Read,
+{
+    type Item = Snapshot;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().ok().flatten()
+    }
+}
+
+fn write_header<W>(writer: &mut W, magic: &[u8; 4], snapshot: &Snapshot) -> io::Result<()>
+where
+    W: Write,
+{
+    writer.write_all(magic)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+
+    for edge in [
+        snapshot.canvas.left(),
+        snapshot.canvas.bottom(),
+        snapshot.canvas.right(),
+        snapshot.canvas.top(),
+    ]
+    .iter()
+    {
+        writer.write_all(&edge.to_le_bytes())?;
+    }
+
+    writer.write_all(&snapshot.resolution.to_le_bytes())?;
+    writer.write_all(&snapshot.rows.to_le_bytes())?;
+    writer.write_all(&snapshot.columns.to_le_bytes())
+}
+
+fn write_frame<W>(writer: &mut W, snapshot: &Snapshot) -> io::Result<()>
+where
+    W: Write,
+{
+    match &snapshot.noise {
+        None => writer.write_all(&[0u8])?,
+        Some(noise) => {
+            writer.write_all(&[1u8])?;
+            writer.write_all(&noise.seed.to_le_bytes())?;
+            writer.write_all(&noise.z_offset.to_le_bytes())?;
+            writer.write_all(&noise.xy_increment.to_le_bytes())?;
+            writer.write_all(&noise.z_increment.to_le_bytes())?;
+        }
+    }
+
+    for vector in &snapshot.vectors {
+        writer.write_all(&vector.x.to_le_bytes())?;
+        writer.write_all(&vector.y.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Reads a header, returning it as a snapshot without any vectors.
+fn read_header<R>(reader: &mut R, magic: &[u8; 4]) -> io::Result<Snapshot>
+where
+    R: Read,
+{
+    let mut found_magic = [0u8; 4];
+    reader.read_exact(&mut found_magic)?;
+
+    if &found_magic != magic {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "The file is not a flowfield file of the expected kind.",
+        ));
+    }
+
+    let mut version = [0u8; 2];
+    reader.read_exact(&mut version)?;
+
+    if u16::from_le_bytes(version) != VERSION {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "The flowfield file has an unsupported version.",
+        ));
+    }
+
+    let (left, bottom, right, top) = (
+        read_f32(reader)?,
+        read_f32(reader)?,
+        read_f32(reader)?,
+        read_f32(reader)?,
+    );
+
+    Ok(Snapshot {
+        canvas: Rect::from_corners(pt2(left, bottom), pt2(right, top)),
+        resolution: read_u32(reader)?,
+        rows: read_u32(reader)?,
+        columns: read_u32(reader)?,
+        noise: None,
+        vectors: vec![],
+    })
+}
+
+/// Reads a frame, returns None if the end of the file is reached before the frame starts.
+fn read_frame<R>(reader: &mut R, header: &Snapshot) -> io::Result<Option<Snapshot>>
+where
+    R: Read,
+{
+    let mut has_noise = [0u8; 1];
+
+    match reader.read_exact(&mut has_noise) {
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        result => result?,
+    }
+
+    let noise = match has_noise[0] {
+        0 => None,
+        _ => Some(NoiseParameters {
+            seed: read_u32(reader)?,
+            z_offset: read_f64(reader)?,
+            xy_increment: read_f64(reader)?,
+            z_increment: read_f64(reader)?,
+        }),
+    };
+
+    let count = header.rows.checked_mul(header.columns).ok_or_else(|| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            "The flowfield file has too many cells.",
+        )
+    })?;
+
+    let vectors = (0..count)
+        .map(|_| Ok(Vector2::new(read_f32(reader)?, read_f32(reader)?)))
+        .collect::<io::Result<Vec<Vector2>>>()?;
+
+    Ok(Some(Snapshot {
+        noise,
+        vectors,
+        ..header.clone()
+    }))
+}
+
+fn read_u32<R>(reader: &mut R) -> io::Result<u32>
+where
+    R: Read,
+{
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f32<R>(reader: &mut R) -> io::Result<f32>
+where
+    R: Read,
+{
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(f32::from_le_bytes(bytes))
+}
+
+fn read_f64<R>(reader: &mut R) -> io::Result<f64>
+where
+    R: Read,
+{
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(f64::from_le_bytes(bytes))
+}
@@ -0,0 +1,377 @@
+//! Provides an implementation of Jos Stam's "Stable Fluids" solver.
+//!
+//! This module exposes a struct, [`Fluid`], which holds a grid of velocities and dye densities
+//! laid over a canvas. Every step of the simulation diffuses and advects the velocities and the
+//! dye and projects the velocities so that the fluid stays mass conserving.
+//!
+//! The grid is measured in cells, velocities are in cells per second, diffusion and viscosity
+//! in cells squared per second. The fluid implements [`VectorField`], so it can be sampled
+//! for vectors, in canvas units per second, exactly like a flowfield.
+use super::flowfield::VectorField;
+use nannou::{
+    geom::{Point2, Rect, Vector2},
+    image::{GrayImage, Luma},
+};
+use ndarray::{Array, Ix2};
+
+/// Enumeration of the quantities which the boundary conditions are set for.
+///
+/// The horizontal velocity is mirrored on the left and right walls, the vertical one on the
+/// top and bottom walls, scalar quantities are simply continued into the walls.
+#[derive(Copy, Clone)]
+enum Boundary {
+    Scalar,
+    Horizontal,
+    Vertical,
+}
+
+/// Encapsulates the state of a grid based fluid simulation.
+///
+/// The internal arrays are indexed by column and row, starting from the bottom left cell
+/// of the canvas, and have an additional layer of cells around them which act as walls.
+pub struct Fluid {
+    columns: usize,
+    rows: usize,
+    resolution: u32,
+    pub canvas: Rect,
+    diffusion: f32,
+    viscosity: f32,
+    iterations: u32,
+    u: Array<f32, Ix2>,
+    v: Array<f32, Ix2>,
+    u_source: Array<f32, Ix2>,
+    v_source: Array<f32, Ix2>,
+    density: Array<f32, Ix2>,
+    density_source: Array<f32, Ix2>,
+}
+
+impl Fluid {
+    /// Constructs a new instance of [`Fluid`], at rest and without any dye.
+    ///
+    /// The number of iterations controls the precision of the linear solver
+    /// used by the diffusion and projection steps. The grid has at least one cell,
+    /// even if the canvas has no width or height.
+    pub fn new(
+        canvas: Rect,
+        resolution: u32,
+        diffusion: f32,
+        viscosity: f32,
+        iterations: u32,
+    ) -> Self {
+        let columns = ((canvas.w() / resolution as f32).ceil() as usize).max(1);
+        let rows = ((canvas.h() / resolution as f32).ceil() as usize).max(1);
+
+        let zeros = Array::<f32, Ix2>::zeros((columns + 2, rows + 2));
+
+        Self {
+            columns,
+            rows,
+            resolution,
+            canvas,
+            diffusion,
+            viscosity,
+            iterations,
+            u: zeros.clone(),
+            v: zeros.clone(),
+            u_source: zeros.clone(),
+            v_source: zeros.clone(),
+            density: zeros.clone(),
+            density_source: zeros,
+        }
+    }
+
+    /// Adds a force, in cells per second squared, to the cell which contains the point.
+    ///
+    /// Forces are accumulated until the next call to [`Fluid::step`].
+    pub fn add_force(&mut self, point: &Point2, force: &Vector2) {
+        if let Some((column, row)) = self.calculate_cell_indices(point) {
+            self.u_source[[column, row]] += force.x;
+            self.v_source[[column, row]] += force.y;
+        }
+    }
+
+    /// Adds dye, per second, to the cell which contains the point.
+    ///
+    /// Dye is accumulated until the next call to [`Fluid::step`].
+    pub fn add_dye(&mut self, point: &Point2, amount: f32) {
+        if let Some((column, row)) = self.calculate_cell_indices(point) {
+            self.density_source[[column, row]] += amount;
+        }
+    }
+
+    /// Advances the simulation by dt seconds.
+    ///
+    /// Applies the accumulated forces and dye, then updates the velocities and afterwards the
+    /// density of the dye, which is carried by the new velocities.
+    pub fn step(&mut self, dt: f32) {
+        self.step_velocity(dt);
+        self.step_density(dt);
+
+        self.u_source.fill(0.0);
+        self.v_source.fill(0.0);
+        self.density_source.fill(0.0);
+    }
+
+    /// Returns the density of the dye in the cell which contains the point.
+    pub fn get_density_at(&self, point: &Point2) -> Option<f32> {
+        self.calculate_cell_indices(point)
+            .map(|(column, row)| self.density[[column, row]])
+    }
+
+    /// Renders the density of the dye as a grayscale image with one pixel per cell.
+    ///
+    /// Densities are clamped to the range [0..1], the top row of the image corresponds to
+    /// the top row of the grid.
+    pub fn density_image(&self) -> GrayImage {
+        GrayImage::from_fn(self.columns as u32, self.rows as u32, |x, y| {
+            let density = self.density[[x as usize + 1, self.rows - y as usize]];
+
+            Luma([(density.clamp(0.0, 1.0) * 255.0) as u8])
+        })
+    }
+
+    fn calculate_cell_indices(&self, point: &Point2) -> Option<(usize, usize)> {
+        match self.canvas.contains(*point) {
+            true => {
+                let column = ((point.x - self.canvas.left()) / self.resolution as f32) as usize;
+                let row = ((point.y - self.canvas.bottom()) / self.resolution as f32) as usize;
+
+                Some((column.min(self.columns - 1) + 1, row.min(self.rows - 1) + 1))
+            }
+            false => None,
+        }
+    }
+
+    fn step_velocity(&mut self, dt: f32) {
+        add_source(&mut self.u, &self.u_source, dt);
+        add_source(&mut self.v, &self.v_source, dt);
+
+        let u_previous = self.u.clone();
+        let v_previous = self.v.clone();
+
+        diffuse(
+            Boundary::Horizontal,
+            &mut self.u,
+            &u_previous,
+            self.viscosity * dt,
+            self.iterations,
+        );
+        diffuse(
+            Boundary::Vertical,
+            &mut self.v,
+            &v_previous,
+            self.viscosity * dt,
+            self.iterations,
+        );
+
+        project(&mut self.u, &mut self.v, self.iterations);
+
+        let u = advect(Boundary::Horizontal, &self.u, &self.u, &self.v, dt);
+        let v = advect(Boundary::Vertical, &self.v, &self.u, &self.v, dt);
+
+        self.u = u;
+        self.v = v;
+
+        project(&mut self.u, &mut self.v, self.iterations);
+    }
+
+    fn step_density(&mut self, dt: f32) {
+        add_source(&mut self.density, &self.density_source, dt);
+
+        let density_previous = self.density.clone();
+
+        diffuse(
+            Boundary::Scalar,
+            &mut self.density,
+            &density_previous,
+            self.diffusion * dt,
+            self.iterations,
+        );
+
+        self.density = advect(Boundary::Scalar, &self.density, &self.u, &self.v, dt);
+    }
+}
+
+impl VectorField for Fluid {
+    /// Returns the velocity of the fluid at the point, bilinearly interpolated
+    /// between the centres of the surrounding cells.
+    fn sample(&self, point: &Point2) -> Option<Vector2> {
+        match self.canvas.contains(*point) {
+            true => {
+                let x = (point.x - self.canvas.left()) / self.resolution as f32 + 0.5;
+                let y = (point.y - self.canvas.bottom()) / self.resolution as f32 + 0.5;
+
+                Some(
+                    Vector2::new(interpolate(&self.u, x, y), interpolate(&self.v, x, y))
+                        * self.resolution as f32,
+                )
+            }
+            false => None,
+        }
+    }
+}
+
+fn add_source(x: &mut Array<f32, Ix2>, source: &Array<f32, Ix2>, dt: f32) {
+    x.zip_mut_with(source, |x, s| *x += dt * s);
+}
+
+/// Sets the values of the wall cells, so that no velocity flows through the walls.
+fn set_boundary(boundary: Boundary, x: &mut Array<f32, Ix2>) {
+    let shape = x.shape();
+    let (columns, rows) = (shape[0] - 2, shape[1] - 2);
+
+    for row in 1..=rows {
+        let (left, right) = (x[[1, row]], x[[columns, row]]);
+
+        match boundary {
+            Boundary::Horizontal => {
+                x[[0, row]] = -left;
+                x[[columns + 1, row]] = -right;
+            }
+            _ => {
+                x[[0, row]] = left;
+                x[[columns + 1, row]] = right;
+            }
+        }
+    }
+
+    for column in 1..=columns {
+        let (bottom, top) = (x[[column, 1]], x[[column, rows]]);
+
+        match boundary {
+            Boundary::Vertical => {
+                x[[column, 0]] = -bottom;
+                x[[column, rows + 1]] = -top;
+            }
+            _ => {
+                x[[column, 0]] = bottom;
+                x[[column, rows + 1]] = top;
+            }
+        }
+    }
+
+    x[[0, 0]] = 0.5 * (x[[1, 0]] + x[[0, 1]]);
+    x[[0, rows + 1]] = 0.5 * (x[[1, rows + 1]] + x[[0, rows]]);
+    x[[columns + 1, 0]] = 0.5 * (x[[columns, 0]] + x[[columns + 1, 1]]);
+    x[[columns + 1, rows + 1]] = 0.5 * (x[[columns, rows + 1]] + x[[columns + 1, rows]]);
+}
+
+/// Solves the linear system of the diffusion and projection steps with Gauss-Seidel relaxation.
+fn solve_linear(
+    boundary: Boundary,
+    x: &mut Array<f32, Ix2>,
+    x_previous: &Array<f32, Ix2>,
+    a: f32,
+    c: f32,
+    iterations: u32,
+) {
+    let shape = x.shape();
+    let (columns, rows) = (shape[0] - 2, shape[1] - 2);
+
+    for _ in 0..iterations {
+        for row in 1..=rows {
+            for column in 1..=columns {
+                x[[column, row]] = (x_previous[[column, row]]
+                    + a * (x[[column - 1, row]]
+                        + x[[column + 1, row]]
+                        + x[[column, row - 1]]
+                        + x[[column, row + 1]]))
+                    / c;
+            }
+        }
+
+        set_boundary(boundary, x);
+    }
+}
+
+/// Diffuses a quantity, rate being the diffusion (or viscosity) multiplied by the time step.
+fn diffuse(
+    boundary: Boundary,
+    x: &mut Array<f32, Ix2>,
+    x_previous: &Array<f32, Ix2>,
+    rate: f32,
+    iterations: u32,
+) {
+    solve_linear(boundary, x, x_previous, rate, 1.0 + 4.0 * rate, iterations);
+}
+
+/// Moves a quantity along the velocities by tracing each cell's centre back in time.
+fn advect(
+    boundary: Boundary,
+    d_previous: &Array<f32, Ix2>,
+    u: &Array<f32, Ix2>,
+    v: &Array<f32, Ix2>,
+    dt: f32,
+) -> Array<f32, Ix2> {
+    let shape = d_previous.shape();
+    let (columns, rows) = (shape[0] - 2, shape[1] - 2);
+
+    let mut d = Array::<f32, Ix2>::zeros((columns + 2, rows + 2));
+
+    for row in 1..=rows {
+        for column in 1..=columns {
+            let x = column as f32 - dt * u[[column, row]];
+            let y = row as f32 - dt * v[[column, row]];
+
+            d[[column, row]] = interpolate(d_previous, x, y);
+        }
+    }
+
+    set_boundary(boundary, &mut d);
+
+    d
+}
+
+/// Makes the velocities mass conserving by subtracting the gradient of the pressure field.
+fn project(u: &mut Array<f32, Ix2>, v: &mut Array<f32, Ix2>, iterations: u32) {
+    let shape = u.shape();
+    let (columns, rows) = (shape[0] - 2, shape[1] - 2);
+
+    let mut divergence = Array::<f32, Ix2>::zeros((columns + 2, rows + 2));
+    let mut pressure = Array::<f32, Ix2>::zeros((columns + 2, rows + 2));
+
+    for row in 1..=rows {
+        for column in 1..=columns {
+            divergence[[column, row]] = -0.5
+                * (u[[column + 1, row]] - u[[column - 1, row]] + v[[column, row + 1]]
+                    - v[[column, row - 1]]);
+        }
+    }
+
+    set_boundary(Boundary::Scalar, &mut divergence);
+
+    solve_linear(
+        Boundary::Scalar,
+        &mut pressure,
+        &divergence,
+        1.0,
+        4.0,
+        iterations,
+    );
+
+    for row in 1..=rows {
+        for column in 1..=columns {
+            u[[column, row]] -= 0.5 * (pressure[[column + 1, row]] - pressure[[column - 1, row]]);
+            v[[column, row]] -= 0.5 * (pressure[[column, row + 1]] - pressure[[column, row - 1]]);
+        }
+    }
+
+    set_boundary(Boundary::Horizontal, u);
+    set_boundary(Boundary::Vertical, v);
+}
+
+/// Bilinearly interpolates a quantity at fractional cell coordinates,
+/// clamped to the inside of the walls.
+fn interpolate(x: &Array<f32, Ix2>, column: f32, row: f32) -> f32 {
+    let shape = x.shape();
+    let (columns, rows) = ((shape[0] - 2) as f32, (shape[1] - 2) as f32);
+
+    let column = column.max(0.5).min(columns + 0.5);
+    let row = row.max(0.5).min(rows + 0.5);
+
+    let (c0, r0) = (column.floor() as usize, row.floor() as usize);
+    let (s, t) = (column - c0 as f32, row - r0 as f32);
+
+    (1.0 - s) * ((1.0 - t) * x[[c0, r0]] + t * x[[c0, r0 + 1]])
+        + s * ((1.0 - t) * x[[c0 + 1, r0]] + t * x[[c0 + 1, r0 + 1]])
+}
@@ -1,2 +1,5 @@
+pub mod density;
 pub mod flowfield;
+pub mod fluid;
+pub mod physarum;
 pub mod poisson_disc;
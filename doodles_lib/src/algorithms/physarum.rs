@@ -0,0 +1,235 @@
+//! Provides an agent based simulation of the slime mould Physarum polycephalum,
+//! after Jeff Jones' "Characteristics of pattern formation and evolution in approximations of
+//! Physarum transport networks".
+//!
+//! Agents move over a trail map laid over the canvas. Every step each agent senses the trail
+//! at three points ahead of it, turns towards the strongest one, moves forward and deposits
+//! trail where it lands. The trail map is then diffused and decays, so that the agents gather
+//! along an ever changing network of paths. Both the agents and the trail map wrap around the
+//! edges of the canvas.
+use super::poisson_disc::PoissonDiscSampler;
+use crate::particle::system::Emitter;
+use nannou::{
+    geom::{Point2, Rect, Vector2},
+    image::{GrayImage, Luma},
+    prelude::TAU,
+};
+use ndarray::{Array, Ix2};
+use rand::Rng;
+
+/// The parameters shared by all agents of a [`Physarum`] simulation.
+///
+/// Angles are in radians, distances in canvas units.
+#[derive(Debug, Copy, Clone)]
+pub struct PhysarumParameters {
+    /// The angle between the agent's heading and each of its side sensors.
+    pub sensor_angle: f32,
+    /// The distance between the agent and its sensors.
+    pub sensor_distance: f32,
+    /// The angle by which an agent turns every step.
+    pub rotation_angle: f32,
+    /// The distance which an agent moves every step.
+    pub step_size: f32,
+    /// The amount of trail which an agent deposits every step.
+    pub deposit: f32,
+    /// The fraction of the trail which evaporates every step, in the range [0..1].
+    pub decay: f32,
+    /// How much of the trail is replaced by the mean of its 3x3 neighbourhood every step,
+    /// in the range [0..1].
+    pub diffusion: f32,
+}
+
+impl Default for PhysarumParameters {
+    fn default() -> Self {
+        Self {
+            sensor_angle: TAU / 8.0,
+            sensor_distance: 9.0,
+            rotation_angle: TAU / 8.0,
+            step_size: 1.0,
+            deposit: 5.0,
+            decay: 0.1,
+            diffusion: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Agent {
+    pub position: Point2,
+    pub heading: f32,
+}
+
+/// Encapsulates the agents and the trail map of a Physarum simulation.
+///
+/// The trail map is indexed by column and row, starting from the bottom left cell
+/// of the canvas.
+pub struct Physarum {
+    pub parameters: PhysarumParameters,
+    canvas: Rect,
+    resolution: u32,
+    columns: usize,
+    rows: usize,
+    trail: Array<f32, Ix2>,
+    agents: Vec<Agent>,
+}
+
+impl Physarum {
+    /// Constructs a new instance of [`Physarum`], without agents and with an empty trail map
+    /// whose cells are resolution canvas units wide.
+    pub fn new(canvas: Rect, resolution: u32, parameters: PhysarumParameters) -> Self {
+        let columns = ((canvas.w() / resolution as f32).ceil() as usize).max(1);
+        let rows = ((canvas.h() / resolution as f32).ceil() as usize).max(1);
+
+        Self {
+            parameters,
+            canvas,
+            resolution,
+            columns,
+            rows,
+            trail: Array::<f32, Ix2>::zeros((columns, rows)),
+            agents: vec![],
+        }
+    }
+
+    pub fn agents(&self) -> &[Agent] {
+        &self.agents
+    }
+
+    pub fn trail(&self) -> &Array<f32, Ix2> {
+        &self.trail
+    }
+
+    /// Adds an agent with a random heading at every point.
+    pub fn spawn_at<I>(&mut self, points: I)
+    where
+        I: IntoIterator<Item = Point2>,
+    {
+        let mut rng = rand::thread_rng();
+
+        self.agents.extend(points.into_iter().map(|position| Agent {
+            position,
+            heading: rng.gen_range(0.0..TAU),
+        }));
+    }
+
+    /// Adds count agents with random headings at positions given by the emitter, e.g. one
+    /// created by [`Emitter::from_image`] to seed the agents on the bright parts of a picture.
+    /// An empty emitter adds no agents.
+    pub fn spawn(&mut self, emitter: &Emitter, count: usize) {
+        self.spawn_at((0..count).filter_map(|_| emitter.emit()));
+    }
+
+    /// Adds an agent at every point of a completed poisson-disc sampling of the canvas.
+    pub fn spawn_poisson(&mut self, r: f32, k: u8) {
+        self.spawn_at(PoissonDiscSampler::sample_domain(self.canvas, r, k));
+    }
+
+    /// Adds trail to the cell which contains the point, e.g. to attract the agents to food.
+    pub fn deposit(&mut self, point: &Point2, amount: f32) {
+        let cell = self.calculate_cell_indices(point);
+        self.trail[cell] += amount;
+    }
+
+    /// Advances the simulation by one step: every agent senses, rotates, moves and deposits,
+    /// then the trail map is diffused and decays.
+    pub fn step(&mut self) {
+        let mut rng = rand::thread_rng();
+        let parameters = self.parameters;
+
+        for index in 0..self.agents.len() {
+            let Agent { position, heading } = self.agents[index];
+
+            let sense = |angle: f32| {
+                let sensor = position + Vector2::from_angle(angle) * parameters.sensor_distance;
+                self.trail[self.calculate_cell_indices(&sensor)]
+            };
+
+            let (left, forward, right) = (
+                sense(heading + parameters.sensor_angle),
+                sense(heading),
+                sense(heading - parameters.sensor_angle),
+            );
+
+            let heading = if forward > left && forward > right {
+                heading
+            } else if forward < left && forward < right {
+                match rng.gen::<bool>() {
+                    true => heading + parameters.rotation_angle,
+                    false => heading - parameters.rotation_angle,
+                }
+            } else if left > right {
+                heading + parameters.rotation_angle
+            } else if right > left {
+                heading - parameters.rotation_angle
+            } else {
+                heading
+            };
+
+            let position =
+                self.wrap(position + Vector2::from_angle(heading) * parameters.step_size);
+
+            self.agents[index] = Agent { position, heading };
+
+            let cell = self.calculate_cell_indices(&position);
+            self.trail[cell] += parameters.deposit;
+        }
+
+        self.diffuse_and_decay();
+    }
+
+    /// Renders the trail map as a grayscale image with one pixel per cell.
+    ///
+    /// The trail is normalized by its maximum, the top row of the image corresponds to
+    /// the top row of the trail map.
+    pub fn trail_image(&self) -> GrayImage {
+        let maximum = self.trail.fold(0.0f32, |maximum, &t| maximum.max(t));
+
+        GrayImage::from_fn(self.columns as u32, self.rows as u32, |x, y| {
+            let trail = match maximum > 0.0 {
+                true => self.trail[[x as usize, self.rows - 1 - y as usize]] / maximum,
+                false => 0.0,
+            };
+
+            Luma([(trail.clamp(0.0, 1.0) * 255.0) as u8])
+        })
+    }
+
+    fn diffuse_and_decay(&mut self) {
+        let (columns, rows) = (self.columns, self.rows);
+        let PhysarumParameters {
+            diffusion, decay, ..
+        } = self.parameters;
+        let trail = &self.trail;
+
+        self.trail = Array::from_shape_fn((columns, rows), |(x, y)| {
+            let mut sum = 0.0;
+
+            for dx in [columns - 1, 0, 1].iter() {
+                for dy in [rows - 1, 0, 1].iter() {
+                    sum += trail[[(x + dx) % columns, (y + dy) % rows]];
+                }
+            }
+
+            let value = trail[[x, y]] * (1.0 - diffusion) + sum / 9.0 * diffusion;
+
+            value * (1.0 - decay)
+        });
+    }
+
+    /// Moves a point which has left the canvas back in from the opposite side.
+    fn wrap(&self, point: Point2) -> Point2 {
+        Point2::new(
+            self.canvas.left() + (point.x - self.canvas.left()).rem_euclid(self.canvas.w()),
+            self.canvas.bottom() + (point.y - self.canvas.bottom()).rem_euclid(self.canvas.h()),
+        )
+    }
+
+    fn calculate_cell_indices(&self, point: &Point2) -> [usize; 2] {
+        let point = self.wrap(*point);
+
+        let column = ((point.x - self.canvas.left()) / self.resolution as f32) as usize;
+        let row = ((point.y - self.canvas.bottom()) / self.resolution as f32) as usize;
+
+        [column.min(self.columns - 1), row.min(self.rows - 1)]
+    }
+}
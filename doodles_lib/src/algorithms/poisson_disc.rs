@@ -119,6 +119,21 @@ impl PoissonDiscSampler {
         }
     }
 
+    /// Runs a complete poisson-disc sampling of the domain and returns all of its points,
+    /// including the random point which the sampling starts from.
+    pub fn sample_domain(domain: Rect, r: f32, k: u8) -> Vec<Point2> {
+        let mut poisson_disc_sampler = Self::new(domain, r, k);
+        let mut points = poisson_disc_sampler.active_points.clone();
+
+        while !poisson_disc_sampler.is_finished() {
+            if let Some(p) = poisson_disc_sampler.sample() {
+                points.push(p);
+            }
+        }
+
+        points
+    }
+
     /// Samples a new point by getting a random active point and generating a sample candidate
     /// positioned somewhere in the spherical annulus between r and 2r.
     ///
@@ -1,3 +1,6 @@
+use nannou::geom::{pt2, Point2, Rect};
+use std::collections::HashMap;
+
 pub trait Initializer<T, U, F> {
     fn initialize(count: usize, f: F) -> T;
 }
@@ -11,3 +14,89 @@ where
         (0..count).map(f).collect::<T>()
     }
 }
+
+/// Buckets indices of points into a grid of square cells, so that the points close to a
+/// position can be found without visiting all of them.
+///
+/// The hash stores indices only, the points themselves stay in the caller's collection.
+#[derive(Debug, Clone)]
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    /// Constructs a new, empty, instance of [`SpatialHash`].
+    ///
+    /// Queries are fastest when the cell size is close to the radius which is queried the most.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Constructs a spatial hash holding the index of every point.
+    pub fn from_points<'a, I>(points: I, cell_size: f32) -> Self
+    where
+        I: IntoIterator<Item = &'a Point2>,
+    {
+        let mut spatial_hash = Self::new(cell_size);
+
+        for (index, point) in points.into_iter().enumerate() {
+            spatial_hash.insert(index, point);
+        }
+
+        spatial_hash
+    }
+
+    pub fn insert(&mut self, index: usize, point: &Point2) {
+        let cell = self.calculate_cell(point);
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    /// Inserts the index into every cell which overlaps the rect, so that shapes with an extent
+    /// are found by the queries around any of their parts.
+    pub fn insert_rect(&mut self, index: usize, rect: &Rect) {
+        let (left, bottom) = self.calculate_cell(&rect.bottom_left());
+        let (right, top) = self.calculate_cell(&rect.top_right());
+
+        for x in left..=right {
+            for y in bottom..=top {
+                self.cells.entry((x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    /// Removes all indices, but keeps the cells which held any for reuse. Cells which have
+    /// stayed empty since the previous call are dropped, so that points which wander across the
+    /// plane don't leave a trail of allocated cells behind.
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, indices| !indices.is_empty());
+
+        for indices in self.cells.values_mut() {
+            indices.clear();
+        }
+    }
+
+    /// Returns the indices of all points in the cells which overlap the square around the
+    /// circle, a superset of the points inside of the circle. Indices inserted with
+    /// [`SpatialHash::insert_rect`] can be returned more than once.
+    pub fn query(&self, center: &Point2, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let (left, bottom) = self.calculate_cell(&pt2(center.x - radius, center.y - radius));
+        let (right, top) = self.calculate_cell(&pt2(center.x + radius, center.y + radius));
+
+        (left..=right)
+            .flat_map(move |x| (bottom..=top).map(move |y| (x, y)))
+            .filter_map(move |cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+
+    fn calculate_cell(&self, point: &Point2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+}
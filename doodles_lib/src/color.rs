@@ -38,3 +38,85 @@ impl From<Color> for Rgb8 {
         rgb8(r, g, b)
     }
 }
+
+/// A continuous color gradient, interpolated linearly between color stops.
+#[derive(Debug, Clone)]
+pub struct Gradient {
+    stops: Vec<(f32, Rgb8)>,
+}
+
+impl Gradient {
+    /// Constructs a gradient from stops made of a position, usually in the range [0..1],
+    /// and the color at that position.
+    ///
+    /// Panics if there are no stops.
+    pub fn new(mut stops: Vec<(f32, Rgb8)>) -> Self {
+        assert!(!stops.is_empty(), "A gradient needs at least one stop.");
+
+        stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        Self { stops }
+    }
+
+    /// Constructs a gradient which spreads the colors evenly over the range [0..1].
+    pub fn evenly_spaced(colors: &[Rgb8]) -> Self {
+        let last = (colors.len().max(2) - 1) as f32;
+
+        Self::new(
+            colors
+                .iter()
+                .enumerate()
+                .map(|(i, color)| (i as f32 / last, *color))
+                .collect(),
+        )
+    }
+
+    /// Returns the color of the gradient at a position, positions outside of the stops get
+    /// the color of the closest stop.
+    pub fn at(&self, position: f32) -> Rgb8 {
+        let next = self.stops.iter().position(|(p, _)| *p > position);
+
+        match next {
+            Some(0) => self.stops[0].1,
+            None => self.stops[self.stops.len() - 1].1,
+            Some(i) => {
+                let ((start, a), (end, b)) = (self.stops[i - 1], self.stops[i]);
+                let t = (position - start) / (end - start);
+
+                let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+                rgb8(
+                    mix(a.red, b.red),
+                    mix(a.green, b.green),
+                    mix(a.blue, b.blue),
+                )
+            }
+        }
+    }
+}
+
+/// A discrete color palette, which splits the range [0..1] into equally wide bands, one for
+/// each of its colors.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    colors: Vec<Rgb8>,
+}
+
+impl Palette {
+    /// Constructs a palette of the colors.
+    ///
+    /// Panics if there are no colors.
+    pub fn new(colors: Vec<Rgb8>) -> Self {
+        assert!(!colors.is_empty(), "A palette needs at least one color.");
+
+        Self { colors }
+    }
+
+    /// Returns the color of the band which contains the position, positions outside of the
+    /// range [0..1] get the color of the closest band.
+    pub fn at(&self, position: f32) -> Rgb8 {
+        let index = (position * self.colors.len() as f32).floor().max(0.0) as usize;
+
+        self.colors[index.min(self.colors.len() - 1)]
+    }
+}
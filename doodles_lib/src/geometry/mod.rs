@@ -1 +1,2 @@
 pub(crate) mod coordinates;
+pub(crate) mod polygon;
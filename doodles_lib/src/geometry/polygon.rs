@@ -0,0 +1,93 @@
+use nannou::geom::{Point2, Rect, Vector2};
+
+/// Checks whether a point lies inside of a simple polygon, using the even-odd rule.
+pub fn contains(vertices: &[Point2], point: &Point2) -> bool {
+    let mut inside = false;
+
+    for (i, a) in vertices.iter().enumerate() {
+        let b = &vertices[(i + 1) % vertices.len()];
+
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// Returns the point on the segment between a and b which is the closest to the given point.
+pub fn closest_point_on_segment(a: &Point2, b: &Point2, point: &Point2) -> Point2 {
+    let ab: Vector2 = *b - *a;
+    let length = ab.magnitude2();
+
+    match length > 0.0 {
+        true => *a + ab * ((*point - *a).dot(ab) / length).clamp(0.0, 1.0),
+        false => *a,
+    }
+}
+
+/// Returns the point on the outline of a polygon which is the closest to the given point.
+pub fn closest_point(vertices: &[Point2], point: &Point2) -> Point2 {
+    vertices
+        .iter()
+        .enumerate()
+        .map(|(i, a)| closest_point_on_segment(a, &vertices[(i + 1) % vertices.len()], point))
+        .fold(None, |closest: Option<Point2>, candidate| match closest {
+            Some(c) if (c - *point).magnitude2() <= (candidate - *point).magnitude2() => Some(c),
+            _ => Some(candidate),
+        })
+        .unwrap_or(*point)
+}
+
+/// Returns the signed area of a simple polygon, positive if its vertices are in
+/// counter-clockwise order.
+pub fn signed_area(vertices: &[Point2]) -> f32 {
+    vertices
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            let b = &vertices[(i + 1) % vertices.len()];
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+/// Returns the centroid of the area of a simple polygon, or the mean of its vertices if
+/// its area is zero.
+pub fn centroid(vertices: &[Point2]) -> Point2 {
+    let area = signed_area(vertices);
+
+    if area == 0.0 {
+        let sum = vertices
+            .iter()
+            .fold(Vector2::zero(), |sum, vertex| sum + *vertex);
+
+        return sum / vertices.len().max(1) as f32;
+    }
+
+    let sum = vertices
+        .iter()
+        .enumerate()
+        .fold(Vector2::zero(), |sum, (i, a)| {
+            let b = &vertices[(i + 1) % vertices.len()];
+            sum + (*a + *b) * (a.x * b.y - b.x * a.y)
+        });
+
+    sum / (6.0 * area)
+}
+
+/// Returns the bounding box of a polygon.
+pub fn bounds(vertices: &[Point2]) -> Rect {
+    vertices
+        .iter()
+        .skip(1)
+        .fold(Rect::from_corners(vertices[0], vertices[0]), |bounds, v| {
+            Rect::from_corners(
+                Point2::new(bounds.left().min(v.x), bounds.bottom().min(v.y)),
+                Point2::new(bounds.right().max(v.x), bounds.top().max(v.y)),
+            )
+        })
+}
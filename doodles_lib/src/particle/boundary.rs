@@ -0,0 +1,164 @@
+//! Provides the policies which decide what happens to a particle that leaves its domain.
+//!
+//! A [`Domain`] is the region particles are supposed to stay in, either a rect or a circle,
+//! and a [`Boundary`] is the behaviour applied to a particle which has left it.
+use super::{system::Emitter, Particle};
+use crate::rand::Samplable;
+use nannou::{
+    geom::{Point2, Rect, Vector2},
+    math::MetricSpace,
+    prelude::TAU,
+};
+use rand::Rng;
+
+/// Enumeration of the regions in which particles can be kept.
+#[derive(Debug, Copy, Clone)]
+pub enum Domain {
+    Rect(Rect),
+    Circle { center: Point2, radius: f32 },
+}
+
+impl Domain {
+    pub fn contains(&self, point: &Point2) -> bool {
+        match self {
+            Domain::Rect(rect) => rect.contains(*point),
+            Domain::Circle { center, radius } => point.distance(*center) <= *radius,
+        }
+    }
+
+    /// Returns the point on the outline of the domain closest to a point outside of it,
+    /// together with the outward normal of the outline at that point.
+    pub fn project(&self, point: &Point2) -> (Point2, Vector2) {
+        match self {
+            Domain::Rect(rect) => {
+                let closest = Point2::new(
+                    point.x.clamp(rect.left(), rect.right()),
+                    point.y.clamp(rect.bottom(), rect.top()),
+                );
+                let offset = *point - closest;
+
+                match offset.x == 0.0 && offset.y == 0.0 {
+                    true => (closest, Vector2::zero()),
+                    false => (closest, offset.normalize()),
+                }
+            }
+            Domain::Circle { center, radius } => {
+                let offset = *point - *center;
+
+                let normal = match offset.x == 0.0 && offset.y == 0.0 {
+                    true => Vector2::new(1.0, 0.0),
+                    false => offset.normalize(),
+                };
+
+                (*center + normal * *radius, normal)
+            }
+        }
+    }
+
+    /// Returns a uniformly distributed random point inside of the domain.
+    pub fn random_point(&self) -> Point2 {
+        match self {
+            Domain::Rect(rect) => Point2::random_from_domain(rect),
+            Domain::Circle { center, radius } => {
+                let mut rng = rand::thread_rng();
+
+                *center
+                    + Vector2::from_angle(rng.gen_range(0.0..TAU))
+                        * (*radius * rng.gen::<f32>().sqrt())
+            }
+        }
+    }
+}
+
+/// Enumeration of the behaviours of particles which leave their domain.
+pub enum Boundary {
+    /// The particle reappears on the opposite side of the domain, see [`Particle::wrap_around`].
+    Wrap,
+    /// The particle bounces off the outline, keeping the restitution fraction (usually in the
+    /// range [0..1]) of its velocity perpendicular to the outline.
+    Reflect { restitution: f32 },
+    /// The particle dies.
+    Kill,
+    /// The particle reappears at a random point inside of the domain.
+    RespawnRandom,
+    /// The particle reappears at a position given by an emitter, or dies if the emitter is empty.
+    RespawnAtEmitter(Emitter),
+    /// The particle stops on the outline.
+    Clamp,
+    /// The particle stays on the outline, keeping only its velocity along the outline.
+    Slide,
+}
+
+/// Enumeration of the results of applying a [`Boundary`] to a particle.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BoundaryOutcome {
+    /// The particle hasn't left its domain.
+    Inside,
+    /// The particle has left its domain and the boundary has been applied.
+    Handled,
+    /// The particle has left its domain and should be removed.
+    Killed,
+}
+
+impl Boundary {
+    /// Applies the boundary to a particle, if it has left the domain.
+    pub fn apply(&self, particle: &mut Particle, domain: &Domain) -> BoundaryOutcome {
+        if domain.contains(&particle.position) {
+            return BoundaryOutcome::Inside;
+        }
+
+        let (closest, normal) = domain.project(&particle.position);
+
+        match self {
+            Boundary::Wrap => match domain {
+                Domain::Rect(rect) => particle.wrap_around(rect),
+                Domain::Circle { center, .. } => {
+                    // The particle reenters at the diametrically opposite point of the outline.
+                    particle.position = *center - (closest - *center);
+                    particle.teleport();
+                }
+            },
+            Boundary::Reflect { restitution } => {
+                let reflected = closest - (particle.position - closest);
+
+                particle.position = match domain.contains(&reflected) {
+                    true => reflected,
+                    false => closest,
+                };
+
+                let outward = particle.velocity.dot(normal);
+
+                if outward > 0.0 {
+                    particle.velocity -= normal * ((1.0 + restitution) * outward);
+                }
+            }
+            Boundary::Kill => return BoundaryOutcome::Killed,
+            Boundary::RespawnRandom => {
+                particle.position = domain.random_point();
+                particle.teleport();
+            }
+            Boundary::RespawnAtEmitter(emitter) => match emitter.emit() {
+                Some(position) => {
+                    particle.position = position;
+                    particle.teleport();
+                }
+                None => return BoundaryOutcome::Killed,
+            },
+            Boundary::Clamp => {
+                particle.position = closest;
+                particle.velocity = Vector2::zero();
+            }
+            Boundary::Slide => {
+                particle.position = closest;
+
+                let outward = particle.velocity.dot(normal);
+
+                if outward > 0.0 {
+                    particle.velocity -= normal * outward;
+                }
+            }
+        }
+
+        BoundaryOutcome::Handled
+    }
+}
@@ -0,0 +1,308 @@
+//! Provides collisions of particles with static obstacles and with each other.
+//!
+//! Particles are treated as discs of a common radius. They collide with the same
+//! [`Obstacle`]s which flowfields can be steered around, and optionally with each other.
+//! Every collision is reported as a [`CollisionEvent`], e.g. so that a doodle can draw a mark
+//! at the point of impact, and answered with a [`CollisionResponse`].
+use super::Particle;
+use crate::{algorithms::flowfield::obstacles::Obstacle, collections::SpatialHash};
+use nannou::geom::{Point2, Vector2};
+
+/// Enumeration of the ways in which a particle reacts to a collision.
+#[derive(Debug, Copy, Clone)]
+pub enum CollisionResponse {
+    /// The particle is pushed out of the obstacle and bounces off it, keeping the restitution
+    /// fraction (usually in the range [0..1]) of its velocity along the normal.
+    Bounce { restitution: f32 },
+    /// The particle stops at the point of contact.
+    Stick,
+    /// The particle dies.
+    Kill,
+}
+
+/// Enumeration of the things a particle can collide with.
+///
+/// Obstacles are never removed, so their indices stay valid. Particles die and are replaced,
+/// so another particle is described by its state at the time of the collision instead.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Collider {
+    /// The obstacle with the index.
+    Obstacle(usize),
+    /// Another particle, with its position and velocity before the collision.
+    Particle { position: Point2, velocity: Vector2 },
+}
+
+/// Describes a single collision.
+#[derive(Debug, Copy, Clone)]
+pub struct CollisionEvent {
+    /// The position of the colliding particle before the collision.
+    pub position: Point2,
+    /// The velocity of the colliding particle before the collision.
+    pub velocity: Vector2,
+    pub collider: Collider,
+    /// The point of contact, on the outline of the collider.
+    pub point: Point2,
+    /// The normal of the collider's outline at the point of contact, pointing to the particle.
+    pub normal: Vector2,
+    /// The speed at which the particle and the collider approached each other.
+    pub speed: f32,
+}
+
+/// Enumeration of the results of handling the collisions of a particle.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum CollisionOutcome {
+    None,
+    Bounced,
+    Stuck,
+    Killed,
+}
+
+/// A set of static obstacles, and the rules for collisions of particles with them and with
+/// each other.
+///
+/// The obstacles are bucketed into a [`SpatialHash`], so that every particle only visits the
+/// obstacles which are close to it.
+pub struct Collisions {
+    obstacles: Vec<Obstacle>,
+    obstacle_response: CollisionResponse,
+    particle_radius: f32,
+    particle_response: Option<CollisionResponse>,
+    spatial_hash: SpatialHash,
+}
+
+impl Collisions {
+    /// Constructs a new instance of [`Collisions`], for particles of the radius, which only
+    /// collide with the obstacles, see [`Collisions::with_particle_collisions`].
+    ///
+    /// Panics if a polygonal obstacle has fewer than three vertices, see [`Obstacle::polygon`].
+    pub fn new(
+        obstacles: Vec<Obstacle>,
+        response: CollisionResponse,
+        particle_radius: f32,
+    ) -> Self {
+        // Invalid obstacles are skipped here, add_obstacle rejects them with a clear message.
+        let cell_size = obstacles
+            .iter()
+            .filter(|o| o.is_valid())
+            .map(|o| o.bounds().w().max(o.bounds().h()))
+            .fold(particle_radius * 2.0, f32::max)
+            .max(1.0);
+
+        let mut collisions = Self {
+            obstacles: vec![],
+            obstacle_response: response,
+            particle_radius,
+            particle_response: None,
+            spatial_hash: SpatialHash::new(cell_size),
+        };
+
+        for obstacle in obstacles {
+            collisions.add_obstacle(obstacle);
+        }
+
+        collisions
+    }
+
+    /// Makes the particles collide with each other as well.
+    pub fn with_particle_collisions(mut self, response: CollisionResponse) -> Self {
+        self.particle_response = Some(response);
+        self
+    }
+
+    pub fn obstacles(&self) -> &[Obstacle] {
+        &self.obstacles
+    }
+
+    pub fn particle_radius(&self) -> f32 {
+        self.particle_radius
+    }
+
+    /// Adds an obstacle, e.g. a particle which has stuck, to grow a cluster.
+    ///
+    /// Panics if a polygonal obstacle has fewer than three vertices, see [`Obstacle::polygon`].
+    pub fn add_obstacle(&mut self, obstacle: Obstacle) {
+        assert!(
+            obstacle.is_valid(),
+            "Polygonal obstacles need at least three vertices."
+        );
+
+        self.spatial_hash
+            .insert_rect(self.obstacles.len(), &obstacle.bounds());
+        self.obstacles.push(obstacle);
+    }
+
+    /// Detects and responds to the collisions of the particles, first with the obstacles and
+    /// then with each other, calling on_collision for every collision.
+    ///
+    /// Returns the outcome for every particle, in the same order as the particles. A particle
+    /// which has collided more than once gets the most severe outcome.
+    pub fn handle<C>(
+        &self,
+        particles: &mut [Particle],
+        mut on_collision: C,
+    ) -> Vec<CollisionOutcome>
+    where
+        C: FnMut(&CollisionEvent),
+    {
+        let mut outcomes = vec![CollisionOutcome::None; particles.len()];
+        let mut candidates = vec![];
+
+        for (index, particle) in particles.iter_mut().enumerate() {
+            candidates.clear();
+            candidates.extend(
+                self.spatial_hash
+                    .query(&particle.position, self.particle_radius),
+            );
+            candidates.sort_unstable();
+            candidates.dedup();
+
+            for &obstacle in &candidates {
+                let (distance, normal) =
+                    self.obstacles[obstacle].distance_and_normal(&particle.position);
+
+                if distance >= self.particle_radius {
+                    continue;
+                }
+
+                let event = CollisionEvent {
+                    position: particle.position,
+                    velocity: particle.velocity,
+                    collider: Collider::Obstacle(obstacle),
+                    point: particle.position - normal * distance,
+                    normal,
+                    speed: -particle.velocity.dot(normal),
+                };
+
+                on_collision(&event);
+
+                let outcome = respond(
+                    particle,
+                    &event,
+                    self.particle_radius,
+                    self.obstacle_response,
+                );
+                outcomes[index] = max_outcome(outcomes[index], outcome);
+            }
+        }
+
+        if let Some(response) = self.particle_response {
+            self.handle_particle_collisions(particles, response, &mut outcomes, &mut on_collision);
+        }
+
+        outcomes
+    }
+
+    fn handle_particle_collisions<C>(
+        &self,
+        particles: &mut [Particle],
+        response: CollisionResponse,
+        outcomes: &mut [CollisionOutcome],
+        on_collision: &mut C,
+    ) where
+        C: FnMut(&CollisionEvent),
+    {
+        let diameter = self.particle_radius * 2.0;
+        let spatial_hash =
+            SpatialHash::from_points(particles.iter().map(|p| &p.position), diameter);
+
+        for index in 0..particles.len() {
+            let position = particles[index].position;
+
+            for other in spatial_hash.query(&position, diameter) {
+                // Every pair is handled once, by the particle with the lower index.
+                if other <= index {
+                    continue;
+                }
+
+                let offset = position - particles[other].position;
+                let distance = offset.magnitude();
+
+                if distance >= diameter {
+                    continue;
+                }
+
+                let normal = match distance > 0.0 {
+                    true => offset / distance,
+                    false => Vector2::new(1.0, 0.0),
+                };
+                let relative_velocity = particles[index].velocity - particles[other].velocity;
+
+                let event = CollisionEvent {
+                    position,
+                    velocity: particles[index].velocity,
+                    collider: Collider::Particle {
+                        position: particles[other].position,
+                        velocity: particles[other].velocity,
+                    },
+                    point: particles[other].position + normal * (distance / 2.0),
+                    normal,
+                    speed: -relative_velocity.dot(normal),
+                };
+
+                on_collision(&event);
+
+                let outcome = match response {
+                    CollisionResponse::Bounce { restitution } => {
+                        // Both particles move half of the overlap apart and, having equal masses,
+                        // exchange the part of their velocities along the normal.
+                        let correction = normal * ((diameter - distance) / 2.0);
+                        particles[index].position += correction;
+                        particles[other].position -= correction;
+
+                        if event.speed > 0.0 {
+                            let impulse = normal * ((1.0 + restitution) * event.speed / 2.0);
+                            particles[index].velocity += impulse;
+                            particles[other].velocity -= impulse;
+                        }
+
+                        CollisionOutcome::Bounced
+                    }
+                    CollisionResponse::Stick => {
+                        particles[index].velocity = Vector2::zero();
+                        particles[other].velocity = Vector2::zero();
+
+                        CollisionOutcome::Stuck
+                    }
+                    CollisionResponse::Kill => CollisionOutcome::Killed,
+                };
+
+                outcomes[index] = max_outcome(outcomes[index], outcome);
+                outcomes[other] = max_outcome(outcomes[other], outcome);
+            }
+        }
+    }
+}
+
+/// Responds to the collision of a particle with a static obstacle.
+fn respond(
+    particle: &mut Particle,
+    event: &CollisionEvent,
+    radius: f32,
+    response: CollisionResponse,
+) -> CollisionOutcome {
+    match response {
+        CollisionResponse::Bounce { restitution } => {
+            particle.position = event.point + event.normal * radius;
+
+            if event.speed > 0.0 {
+                particle.velocity += event.normal * ((1.0 + restitution) * event.speed);
+            }
+
+            CollisionOutcome::Bounced
+        }
+        CollisionResponse::Stick => {
+            particle.position = event.point + event.normal * radius;
+            particle.velocity = Vector2::zero();
+
+            CollisionOutcome::Stuck
+        }
+        CollisionResponse::Kill => CollisionOutcome::Killed,
+    }
+}
+
+fn max_outcome(a: CollisionOutcome, b: CollisionOutcome) -> CollisionOutcome {
+    match a < b {
+        true => b,
+        false => a,
+    }
+}
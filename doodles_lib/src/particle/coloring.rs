@@ -0,0 +1,131 @@
+//! Provides color mappers, which recolor particles every frame from their motion or from
+//! a picture.
+//!
+//! A mapper turns a [`Property`] of a particle into a position in the range [0..1] and looks
+//! it up in a [`ColorScale`], a continuous gradient or a discrete palette, or it takes the color
+//! of a picture under the particle. Mappers only change the red, green and blue channels, the
+//! particle's alpha is kept.
+use super::Particle;
+use crate::color::{Gradient, Palette};
+use nannou::{
+    color::{rgb8, rgba8, Rgb8},
+    geom::{Point2, Rect},
+    image::{DynamicImage, GrayImage, RgbImage},
+    prelude::{PI, TAU},
+};
+
+/// Enumeration of the properties of a particle which can be mapped to a color.
+#[derive(Debug, Clone)]
+pub enum Property {
+    /// The magnitude of the velocity, divided by max.
+    Speed { max: f32 },
+    /// The angle of the velocity, with the range [-π..π] mapped to [0..1].
+    Heading,
+    /// The age of the particle divided by max, or by its lifetime if max is None.
+    Age { max: Option<u32> },
+    /// The length of the path travelled by the particle, divided by max.
+    Distance { max: f32 },
+    /// The luminance of a picture stretched over the canvas, at the particle's position.
+    Luminance { picture: GrayImage, canvas: Rect },
+}
+
+impl Property {
+    /// Returns the value of the property for a particle, usually in the range [0..1].
+    pub fn value(&self, particle: &Particle) -> f32 {
+        match self {
+            Property::Speed { max } => particle.velocity().magnitude() / max,
+            Property::Heading => (particle.velocity().angle() + PI) / TAU,
+            Property::Age { max } => match max.or(particle.lifetime) {
+                Some(max) if max > 0 => particle.age as f32 / max as f32,
+                _ => 0.0,
+            },
+            Property::Distance { max } => particle.distance() / max,
+            Property::Luminance { picture, canvas } => {
+                let (width, height) = picture.dimensions();
+
+                match calculate_pixel(width, height, canvas, &particle.position) {
+                    Some((x, y)) => picture.get_pixel(x, y)[0] as f32 / 255.0,
+                    None => 0.0,
+                }
+            }
+        }
+    }
+}
+
+/// Enumeration of the ways in which a value in the range [0..1] is turned into a color.
+#[derive(Debug, Clone)]
+pub enum ColorScale {
+    Gradient(Gradient),
+    Palette(Palette),
+}
+
+impl ColorScale {
+    pub fn at(&self, position: f32) -> Rgb8 {
+        match self {
+            ColorScale::Gradient(gradient) => gradient.at(position),
+            ColorScale::Palette(palette) => palette.at(position),
+        }
+    }
+}
+
+/// Enumeration of the ways in which particles are colored.
+#[derive(Debug, Clone)]
+pub enum ColorMapper {
+    /// Looks up a property of the particle in a color scale.
+    Scaled {
+        property: Property,
+        scale: ColorScale,
+    },
+    /// Takes the color of a picture stretched over the canvas, at the particle's position.
+    /// Particles outside of the canvas keep their color.
+    Image { picture: RgbImage, canvas: Rect },
+}
+
+impl ColorMapper {
+    /// Constructs a mapper which colors particles with the picture under them.
+    pub fn from_image(picture: &DynamicImage, canvas: Rect) -> Self {
+        ColorMapper::Image {
+            picture: picture.to_rgb8(),
+            canvas,
+        }
+    }
+
+    /// Returns the color of a particle, or None if the mapper has no color for it.
+    pub fn color(&self, particle: &Particle) -> Option<Rgb8> {
+        match self {
+            ColorMapper::Scaled { property, scale } => Some(scale.at(property.value(particle))),
+            ColorMapper::Image { picture, canvas } => {
+                let (width, height) = picture.dimensions();
+
+                calculate_pixel(width, height, canvas, &particle.position).map(|(x, y)| {
+                    let pixel = picture.get_pixel(x, y);
+
+                    rgb8(pixel[0], pixel[1], pixel[2])
+                })
+            }
+        }
+    }
+
+    /// Recolors a particle, keeping its alpha.
+    pub fn apply(&self, particle: &mut Particle) {
+        if let Some(color) = self.color(particle) {
+            let alpha = particle.color().alpha;
+
+            particle.set_color(rgba8(color.red, color.green, color.blue, alpha));
+        }
+    }
+}
+
+/// Returns the pixel of a picture stretched over the canvas which contains the point,
+/// the picture's rows start at the top of the canvas.
+fn calculate_pixel(width: u32, height: u32, canvas: &Rect, point: &Point2) -> Option<(u32, u32)> {
+    match canvas.contains(*point) && width > 0 && height > 0 {
+        true => {
+            let x = ((point.x - canvas.left()) / canvas.w() * width as f32) as u32;
+            let y = ((canvas.top() - point.y) / canvas.h() * height as f32) as u32;
+
+            Some((x.min(width - 1), y.min(height - 1)))
+        }
+        false => None,
+    }
+}
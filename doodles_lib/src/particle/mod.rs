@@ -0,0 +1,174 @@
+//! Provides a simple particle which can be steered by forces, e.g. the vectors of a flowfield.
+//!
+//! Particles can be used on their own or be managed by a [`system::ParticleSystem`], which
+//! emits them, ages them and respawns them once they die. A particle can also remember its
+//! recent positions in a [`trail::Trail`], so that its path can be drawn on a cleared frame.
+//! Particles which leave their domain are handled by one of the policies in [`boundary`].
+//! Instead of being updated once per frame, particles can also be integrated over time with
+//! a mass and drag, see [`physics`], or flock together, see [`steering`]. Very large numbers
+//! of particles are better kept in a [`store::ParticleStore`], which updates them in parallel.
+//! The color of particles can follow their motion, see [`coloring`], and particles can collide
+//! with obstacles and with each other, see [`collision`].
+use nannou::{
+    Draw,
+    {
+        color::Rgba8,
+        geom::{Point2, Rect, Vector2},
+    },
+};
+
+pub mod boundary;
+pub mod collision;
+pub mod coloring;
+pub mod physics;
+pub mod steering;
+pub mod store;
+pub mod system;
+pub mod trail;
+
+use trail::Trail;
+
+pub struct Particle {
+    pub position: Point2,
+    previous_position: Option<Point2>,
+    velocity: Vector2,
+    acceleration: Vector2,
+    velocity_limit: f32,
+    color: Rgba8,
+    pub age: u32,
+    pub lifetime: Option<u32>,
+    distance: f32,
+    trail: Option<Trail>,
+}
+
+impl Particle {
+    pub fn new(
+        position: Point2,
+        previous_position: Option<Point2>,
+        velocity: Vector2,
+        acceleration: Vector2,
+        velocity_limit: f32,
+        color: Rgba8,
+    ) -> Self {
+        Self {
+            position,
+            previous_position,
+            velocity,
+            acceleration,
+            velocity_limit,
+            color,
+            age: 0,
+            lifetime: None,
+            distance: 0.0,
+            trail: None,
+        }
+    }
+
+    /// Makes the particle remember up to capacity of its most recent positions, which are
+    /// displayed as a trail whose newest segment is weight wide.
+    pub fn with_trail(mut self, capacity: usize, weight: f32) -> Self {
+        let mut trail = Trail::new(capacity, weight);
+        trail.push(self.position);
+
+        self.trail = Some(trail);
+        self
+    }
+
+    pub fn trail(&self) -> Option<&Trail> {
+        self.trail.as_ref()
+    }
+
+    pub fn update(&mut self) {
+        self.velocity += self.acceleration;
+        self.velocity = self.velocity.limit_magnitude(self.velocity_limit);
+        self.move_to(self.position + self.velocity);
+    }
+
+    pub fn velocity(&self) -> &Vector2 {
+        &self.velocity
+    }
+
+    pub fn color(&self) -> Rgba8 {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Rgba8) {
+        self.color = color;
+    }
+
+    /// Returns the length of the path which the particle has travelled, jumps caused by
+    /// wrapping around or respawning are not counted.
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Checks whether the particle has lived for at least as many updates as its lifetime.
+    pub fn is_expired(&self) -> bool {
+        match self.lifetime {
+            None => false,
+            Some(lifetime) => self.age >= lifetime,
+        }
+    }
+
+    /// Draws the particle's trail if it has one, otherwise just the particle's last step.
+    pub fn display(&self, draw: &Draw) {
+        if let Some(trail) = &self.trail {
+            trail.display(draw, self.color);
+        } else if let Some(previous_position) = self.previous_position {
+            draw.line()
+                .color(self.color)
+                .weight(1.0)
+                .points(previous_position, self.position);
+        }
+    }
+
+    pub fn wrap_around(&mut self, canvas: &Rect) {
+        if self.position.x > canvas.right() {
+            self.position.x = canvas.left();
+            self.teleport();
+        }
+
+        if self.position.x < canvas.left() {
+            self.position.x = canvas.right();
+            self.teleport();
+        }
+
+        if self.position.y > canvas.top() {
+            self.position.y = canvas.bottom();
+            self.teleport();
+        }
+
+        if self.position.y < canvas.bottom() {
+            self.position.y = canvas.top();
+            self.teleport();
+        }
+    }
+
+    pub fn apply_force(&mut self, force: &Vector2) {
+        self.acceleration += *force;
+    }
+
+    /// Moves the particle to its next position, clears the applied forces and ages it.
+    fn move_to(&mut self, position: Point2) {
+        self.previous_position = Some(self.position);
+        self.distance += (position - self.position).magnitude();
+        self.position = position;
+        self.acceleration *= 0.0;
+        self.age += 1;
+
+        if let Some(trail) = &mut self.trail {
+            trail.push(self.position);
+        }
+    }
+
+    /// Marks the particle's current position as the start of a new path, so that no line is
+    /// drawn from the position it has jumped from.
+    fn teleport(&mut self) {
+        self.previous_position = Some(self.position);
+
+        if let Some(trail) = &mut self.trail {
+            trail.clear();
+            trail.push(self.position);
+        }
+    }
+}
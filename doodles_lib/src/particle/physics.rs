@@ -0,0 +1,154 @@
+//! Provides physically based integration of a particle's motion.
+//!
+//! [`Particle::update`] adds the accumulated acceleration once per frame, so the motion of
+//! a particle depends on the frame rate. [`Particle::integrate`] instead advances a particle by
+//! a time step, taking its mass and drag into account, with one of several [`Integrator`]s.
+//! Combined with a [`FixedTimestep`] the simulation is decoupled from rendering, so runs at
+//! different frame rates, or without a window at all, produce the same trajectories.
+use super::Particle;
+use crate::algorithms::flowfield::VectorField;
+use nannou::geom::{Point2, Vector2};
+
+/// The physical properties of a particle.
+///
+/// Drag opposes the velocity v of a particle with a force of
+/// `linear_drag * |v| + quadratic_drag * |v|²`.
+#[derive(Debug, Copy, Clone)]
+pub struct Physics {
+    pub mass: f32,
+    pub linear_drag: f32,
+    pub quadratic_drag: f32,
+}
+
+impl Physics {
+    pub fn new(mass: f32, linear_drag: f32, quadratic_drag: f32) -> Self {
+        Self {
+            mass,
+            linear_drag,
+            quadratic_drag,
+        }
+    }
+
+    fn drag(&self, velocity: &Vector2) -> Vector2 {
+        *velocity * -(self.linear_drag + self.quadratic_drag * velocity.magnitude())
+    }
+}
+
+/// Enumeration of the numerical integration methods.
+#[derive(Debug, Copy, Clone)]
+pub enum Integrator {
+    /// Updates the velocity first and then moves the particle with the new velocity.
+    SemiImplicitEuler,
+    /// Velocity Verlet, which averages the acceleration at the start and at the end of a step.
+    Verlet,
+    /// The classic fourth order Runge-Kutta method.
+    RungeKutta4,
+}
+
+impl Particle {
+    /// Advances the particle by dt seconds.
+    ///
+    /// The particle is accelerated by the forces applied since the last step, the force of the
+    /// field at its position and its drag, all divided by its mass. The forces applied with
+    /// [`Particle::apply_force`] are assumed to be constant during the step, while the field and
+    /// the drag are evaluated wherever the integrator needs them. Unlike [`Particle::update`],
+    /// the velocity isn't limited, drag gives every particle a terminal velocity instead.
+    pub fn integrate<F>(&mut self, field: &F, physics: &Physics, integrator: Integrator, dt: f32)
+    where
+        F: VectorField,
+    {
+        let applied = self.acceleration;
+
+        let acceleration = |position: &Point2, velocity: &Vector2| {
+            let field_force = field.sample(position).unwrap_or_else(Vector2::zero);
+
+            (applied + field_force + physics.drag(velocity)) / physics.mass
+        };
+
+        let (position, velocity) = (self.position, self.velocity);
+
+        let (position, velocity) = match integrator {
+            Integrator::SemiImplicitEuler => {
+                let velocity = velocity + acceleration(&position, &velocity) * dt;
+
+                (position + velocity * dt, velocity)
+            }
+            Integrator::Verlet => {
+                let a = acceleration(&position, &velocity);
+                let next_position = position + velocity * dt + a * (0.5 * dt * dt);
+                let next_a = acceleration(&next_position, &(velocity + a * dt));
+
+                (next_position, velocity + (a + next_a) * (0.5 * dt))
+            }
+            Integrator::RungeKutta4 => {
+                let (p1, v1) = (position, velocity);
+                let a1 = acceleration(&p1, &v1);
+
+                let (p2, v2) = (p1 + v1 * (0.5 * dt), v1 + a1 * (0.5 * dt));
+                let a2 = acceleration(&p2, &v2);
+
+                let (p3, v3) = (p1 + v2 * (0.5 * dt), v1 + a2 * (0.5 * dt));
+                let a3 = acceleration(&p3, &v3);
+
+                let (p4, v4) = (p1 + v3 * dt, v1 + a3 * dt);
+                let a4 = acceleration(&p4, &v4);
+
+                (
+                    p1 + (v1 + v2 * 2.0 + v3 * 2.0 + v4) * (dt / 6.0),
+                    v1 + (a1 + a2 * 2.0 + a3 * 2.0 + a4) * (dt / 6.0),
+                )
+            }
+        };
+
+        self.velocity = velocity;
+        self.move_to(position);
+    }
+}
+
+/// Decouples a simulation's fixed time step from the variable time between rendered frames.
+///
+/// The time of every frame is added to an accumulator, which is then consumed in steps
+/// of a fixed size. The number of steps per frame is capped, so that a slow frame doesn't
+/// cause an ever growing backlog of steps.
+#[derive(Debug, Copy, Clone)]
+pub struct FixedTimestep {
+    dt: f32,
+    accumulator: f32,
+    max_steps: u32,
+}
+
+impl FixedTimestep {
+    pub fn new(dt: f32, max_steps: u32) -> Self {
+        Self {
+            dt,
+            accumulator: 0.0,
+            max_steps,
+        }
+    }
+
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// Adds the time of a frame, in seconds, and returns how many steps should be simulated.
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        self.accumulator += frame_time;
+
+        let steps = (self.accumulator / self.dt).floor() as u32;
+        self.accumulator -= steps as f32 * self.dt;
+
+        match steps > self.max_steps {
+            true => {
+                self.accumulator = 0.0;
+                self.max_steps
+            }
+            false => steps,
+        }
+    }
+
+    /// Returns how far, as a fraction of a step, the accumulated time is into the next step,
+    /// which can be used to interpolate between the last two simulated states.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.dt
+    }
+}
@@ -0,0 +1,395 @@
+//! Provides Craig Reynolds' steering behaviours, and a flock of particles which follows them.
+//!
+//! Every behaviour returns a steering force, the difference between a desired velocity and the
+//! particle's current velocity limited to a maximum force. The free functions can steer any
+//! particle, e.g. the ones of a [`super::system::ParticleSystem`], while a [`Flock`] combines
+//! weighted behaviours, including the ones which depend on the neighbours of each agent.
+use super::Particle;
+use crate::{collections::SpatialHash, geometry::polygon};
+use nannou::{
+    geom::{Point2, Vector2},
+    Draw,
+};
+use rand::Rng;
+
+/// An open polyline, together with the radius within which agents are considered on the path.
+#[derive(Debug, Clone)]
+pub struct Path {
+    points: Vec<Point2>,
+    radius: f32,
+}
+
+impl Path {
+    pub fn new(points: Vec<Point2>, radius: f32) -> Self {
+        Self { points, radius }
+    }
+
+    pub fn points(&self) -> &[Point2] {
+        &self.points
+    }
+
+    /// Returns the point of the path closest to the given point, together with the direction of
+    /// the path at that point.
+    fn closest(&self, point: &Point2) -> Option<(Point2, Vector2)> {
+        self.points
+            .windows(2)
+            .map(|segment| {
+                let closest = polygon::closest_point_on_segment(&segment[0], &segment[1], point);
+
+                (closest, segment[1] - segment[0])
+            })
+            .min_by(|(a, _), (b, _)| {
+                (*a - *point)
+                    .magnitude2()
+                    .partial_cmp(&(*b - *point).magnitude2())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+}
+
+/// Enumeration of the steering behaviours of a [`Flock`].
+#[derive(Debug, Clone)]
+pub enum Behaviour {
+    /// Steers away from the neighbours closer than the flock's separation radius.
+    Separation,
+    /// Steers towards the average heading of the neighbours.
+    Alignment,
+    /// Steers towards the average position of the neighbours.
+    Cohesion,
+    /// Steers towards a target at full speed.
+    Seek(Point2),
+    /// Steers away from a target, once it is closer than the radius.
+    Flee { target: Point2, radius: f32 },
+    /// Steers towards a target, slowing down within the slowing radius until it stops on it.
+    Arrive { target: Point2, slowing_radius: f32 },
+    /// Steers towards a point on a circle projected distance ahead of the agent, which is
+    /// randomly moved along the circle by up to jitter radians, in either direction, every update.
+    Wander {
+        distance: f32,
+        radius: f32,
+        jitter: f32,
+    },
+    /// Steers back to the path whenever the agent's position, predicted lookahead ahead,
+    /// leaves it.
+    FollowPath { path: Path, lookahead: f32 },
+}
+
+/// A group of particles steered by weighted behaviours.
+///
+/// The agents are bucketed into a [`SpatialHash`] every update, so that each agent only visits
+/// the agents close to it when looking for its neighbours.
+pub struct Flock {
+    agents: Vec<Particle>,
+    wander_angles: Vec<f32>,
+    behaviours: Vec<(Behaviour, f32)>,
+    neighbour_radius: f32,
+    separation_radius: f32,
+    max_speed: f32,
+    max_force: f32,
+    spatial_hash: SpatialHash,
+}
+
+impl Flock {
+    /// Constructs a new instance of [`Flock`] without any behaviours, see [`Flock::with_behaviour`].
+    pub fn new(
+        agents: Vec<Particle>,
+        neighbour_radius: f32,
+        separation_radius: f32,
+        max_speed: f32,
+        max_force: f32,
+    ) -> Self {
+        Self {
+            wander_angles: vec![0.0; agents.len()],
+            agents,
+            behaviours: vec![],
+            neighbour_radius,
+            separation_radius,
+            max_speed,
+            max_force,
+            spatial_hash: SpatialHash::new(neighbour_radius),
+        }
+    }
+
+    /// Adds a behaviour, whose steering force is multiplied by the weight.
+    pub fn with_behaviour(mut self, behaviour: Behaviour, weight: f32) -> Self {
+        self.behaviours.push((behaviour, weight));
+        self
+    }
+
+    /// Gives access to the behaviours and their weights, e.g. to move a target or to tune
+    /// the weights while the sketch runs.
+    pub fn behaviours_mut(&mut self) -> &mut [(Behaviour, f32)] {
+        &mut self.behaviours
+    }
+
+    pub fn agents(&self) -> &[Particle] {
+        &self.agents
+    }
+
+    pub fn agents_mut(&mut self) -> &mut [Particle] {
+        &mut self.agents
+    }
+
+    pub fn push(&mut self, agent: Particle) {
+        self.agents.push(agent);
+        self.wander_angles.push(0.0);
+    }
+
+    /// Steers every agent with the sum of its weighted behaviours and updates it.
+    pub fn update(&mut self) {
+        self.spatial_hash.clear();
+
+        for (index, agent) in self.agents.iter().enumerate() {
+            self.spatial_hash.insert(index, &agent.position);
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut neighbours = vec![];
+        let mut forces = Vec::with_capacity(self.agents.len());
+
+        let (agents, neighbour_radius) = (&self.agents, self.neighbour_radius);
+
+        for (index, agent) in agents.iter().enumerate() {
+            neighbours.clear();
+            neighbours.extend(
+                self.spatial_hash
+                    .query(&agent.position, neighbour_radius)
+                    .filter(|&other| other != index)
+                    .map(|other| &agents[other])
+                    .filter(|other| {
+                        (other.position - agent.position).magnitude() < neighbour_radius
+                    }),
+            );
+
+            let mut force = Vector2::zero();
+
+            for (behaviour, weight) in &self.behaviours {
+                let steering = match behaviour {
+                    Behaviour::Separation => separation(
+                        agent,
+                        &neighbours,
+                        self.separation_radius,
+                        self.max_speed,
+                        self.max_force,
+                    ),
+                    Behaviour::Alignment => {
+                        alignment(agent, &neighbours, self.max_speed, self.max_force)
+                    }
+                    Behaviour::Cohesion => {
+                        cohesion(agent, &neighbours, self.max_speed, self.max_force)
+                    }
+                    Behaviour::Seek(target) => seek(agent, target, self.max_speed, self.max_force),
+                    Behaviour::Flee { target, radius } => {
+                        flee(agent, target, *radius, self.max_speed, self.max_force)
+                    }
+                    Behaviour::Arrive {
+                        target,
+                        slowing_radius,
+                    } => arrive(
+                        agent,
+                        target,
+                        *slowing_radius,
+                        self.max_speed,
+                        self.max_force,
+                    ),
+                    Behaviour::Wander {
+                        distance,
+                        radius,
+                        jitter,
+                    } => {
+                        let angle = &mut self.wander_angles[index];
+                        *angle += rng.gen_range(-jitter.abs()..=jitter.abs());
+
+                        wander(
+                            agent,
+                            *angle,
+                            *distance,
+                            *radius,
+                            self.max_speed,
+                            self.max_force,
+                        )
+                    }
+                    Behaviour::FollowPath { path, lookahead } => {
+                        follow_path(agent, path, *lookahead, self.max_speed, self.max_force)
+                    }
+                };
+
+                force += steering * *weight;
+            }
+
+            forces.push(force);
+        }
+
+        for (agent, force) in self.agents.iter_mut().zip(forces) {
+            agent.apply_force(&force);
+            agent.update();
+        }
+    }
+
+    pub fn display(&self, draw: &Draw) {
+        for agent in &self.agents {
+            agent.display(draw);
+        }
+    }
+}
+
+/// Returns the force which turns the particle's velocity towards the desired velocity.
+pub fn steer(particle: &Particle, desired: &Vector2, max_force: f32) -> Vector2 {
+    (*desired - particle.velocity).limit_magnitude(max_force)
+}
+
+pub fn seek(particle: &Particle, target: &Point2, max_speed: f32, max_force: f32) -> Vector2 {
+    let desired = with_magnitude_or_zero(*target - particle.position, max_speed);
+
+    steer(particle, &desired, max_force)
+}
+
+/// Steers away from the target, as long as it is closer than the radius.
+pub fn flee(
+    particle: &Particle,
+    target: &Point2,
+    radius: f32,
+    max_speed: f32,
+    max_force: f32,
+) -> Vector2 {
+    let offset = particle.position - *target;
+
+    match offset.magnitude() < radius {
+        true => steer(
+            particle,
+            &with_magnitude_or_zero(offset, max_speed),
+            max_force,
+        ),
+        false => Vector2::zero(),
+    }
+}
+
+/// Steers towards the target, slowing down linearly within the slowing radius.
+pub fn arrive(
+    particle: &Particle,
+    target: &Point2,
+    slowing_radius: f32,
+    max_speed: f32,
+    max_force: f32,
+) -> Vector2 {
+    let offset = *target - particle.position;
+    let distance = offset.magnitude();
+
+    let speed = match distance < slowing_radius {
+        true => max_speed * distance / slowing_radius,
+        false => max_speed,
+    };
+
+    steer(particle, &with_magnitude_or_zero(offset, speed), max_force)
+}
+
+/// Seeks the point at the angle on a circle of the radius, projected distance ahead of the
+/// particle. Randomly changing the angle a little every update makes the particle wander.
+pub fn wander(
+    particle: &Particle,
+    angle: f32,
+    distance: f32,
+    radius: f32,
+    max_speed: f32,
+    max_force: f32,
+) -> Vector2 {
+    let heading = with_magnitude_or_zero(particle.velocity, distance);
+    let target =
+        particle.position + heading + Vector2::from_angle(heading.angle() + angle) * radius;
+
+    seek(particle, &target, max_speed, max_force)
+}
+
+/// Seeks a point lookahead further along the path, whenever the particle's position predicted
+/// lookahead ahead is farther from the path than its radius.
+pub fn follow_path(
+    particle: &Particle,
+    path: &Path,
+    lookahead: f32,
+    max_speed: f32,
+    max_force: f32,
+) -> Vector2 {
+    let predicted = particle.position + with_magnitude_or_zero(particle.velocity, lookahead);
+
+    match path.closest(&predicted) {
+        Some((closest, direction)) if (predicted - closest).magnitude() > path.radius => {
+            let target = closest + with_magnitude_or_zero(direction, lookahead);
+
+            seek(particle, &target, max_speed, max_force)
+        }
+        _ => Vector2::zero(),
+    }
+}
+
+/// Steers away from the neighbours closer than the radius, the closer a neighbour the stronger.
+pub fn separation(
+    particle: &Particle,
+    neighbours: &[&Particle],
+    radius: f32,
+    max_speed: f32,
+    max_force: f32,
+) -> Vector2 {
+    let away = neighbours
+        .iter()
+        .map(|other| particle.position - other.position)
+        .filter(|offset| {
+            let distance = offset.magnitude();
+            distance > 0.0 && distance < radius
+        })
+        .fold(Vector2::zero(), |sum, offset| {
+            sum + offset / offset.magnitude2()
+        });
+
+    match away.x == 0.0 && away.y == 0.0 {
+        true => Vector2::zero(),
+        false => steer(particle, &away.with_magnitude(max_speed), max_force),
+    }
+}
+
+pub fn alignment(
+    particle: &Particle,
+    neighbours: &[&Particle],
+    max_speed: f32,
+    max_force: f32,
+) -> Vector2 {
+    match neighbours.is_empty() {
+        true => Vector2::zero(),
+        false => {
+            let heading = neighbours
+                .iter()
+                .fold(Vector2::zero(), |sum, other| sum + other.velocity);
+
+            steer(
+                particle,
+                &with_magnitude_or_zero(heading, max_speed),
+                max_force,
+            )
+        }
+    }
+}
+
+pub fn cohesion(
+    particle: &Particle,
+    neighbours: &[&Particle],
+    max_speed: f32,
+    max_force: f32,
+) -> Vector2 {
+    match neighbours.is_empty() {
+        true => Vector2::zero(),
+        false => {
+            let center = neighbours
+                .iter()
+                .fold(Vector2::zero(), |sum, other| sum + other.position)
+                / neighbours.len() as f32;
+
+            seek(particle, &center, max_speed, max_force)
+        }
+    }
+}
+
+fn with_magnitude_or_zero(vector: Vector2, magnitude: f32) -> Vector2 {
+    match vector.x == 0.0 && vector.y == 0.0 {
+        true => Vector2::zero(),
+        false => vector.with_magnitude(magnitude),
+    }
+}
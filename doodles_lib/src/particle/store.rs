@@ -0,0 +1,166 @@
+//! Provides a structure-of-arrays container for very large numbers of particles.
+//!
+//! A [`ParticleStore`] keeps the positions, velocities and ages of its particles in contiguous
+//! buffers, instead of a `Vec` of [`super::Particle`]s, and updates them in parallel. Every
+//! particle is updated independently of all others and without any randomness, so a store
+//! created from a seed always produces the same results, no matter how many threads are used.
+use crate::algorithms::flowfield::VectorField;
+use nannou::{
+    color::Rgba8,
+    geom::{Point2, Rect, Vector2},
+    prelude::TAU,
+    Draw,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+/// Holds the state of many particles in separate buffers, one for each property.
+///
+/// The particles behave like a [`super::Particle`] which is steered by a field, updated and
+/// wrapped around every frame, with forces applied as accelerations.
+pub struct ParticleStore {
+    positions: Vec<Point2>,
+    previous_positions: Vec<Point2>,
+    velocities: Vec<Vector2>,
+    ages: Vec<u32>,
+    velocity_limit: f32,
+}
+
+impl ParticleStore {
+    /// Constructs a new, empty, instance of [`ParticleStore`].
+    pub fn new(velocity_limit: f32) -> Self {
+        Self {
+            positions: vec![],
+            previous_positions: vec![],
+            velocities: vec![],
+            ages: vec![],
+            velocity_limit,
+        }
+    }
+
+    /// Constructs a store of count particles at uniformly distributed points of the domain,
+    /// each with a random velocity of magnitude one.
+    ///
+    /// The same seed always produces the same particles.
+    pub fn random(count: usize, domain: &Rect, velocity_limit: f32, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut store = Self::new(velocity_limit);
+
+        store.reserve(count);
+
+        for _ in 0..count {
+            let position = Point2::new(
+                rng.gen_range(domain.left()..=domain.right()),
+                rng.gen_range(domain.bottom()..=domain.top()),
+            );
+
+            store.push(position, Vector2::from_angle(rng.gen_range(0.0..TAU)));
+        }
+
+        store
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.positions.reserve(additional);
+        self.previous_positions.reserve(additional);
+        self.velocities.reserve(additional);
+        self.ages.reserve(additional);
+    }
+
+    pub fn push(&mut self, position: Point2, velocity: Vector2) {
+        self.positions.push(position);
+        self.previous_positions.push(position);
+        self.velocities.push(velocity);
+        self.ages.push(0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn positions(&self) -> &[Point2] {
+        &self.positions
+    }
+
+    pub fn previous_positions(&self) -> &[Point2] {
+        &self.previous_positions
+    }
+
+    pub fn velocities(&self) -> &[Vector2] {
+        &self.velocities
+    }
+
+    pub fn ages(&self) -> &[u32] {
+        &self.ages
+    }
+
+    /// Steers every particle with the vector of the field at its position and moves it,
+    /// in parallel.
+    ///
+    /// Particles for which the field has no vector keep their velocity.
+    pub fn update<F>(&mut self, field: &F)
+    where
+        F: VectorField + Sync,
+    {
+        let velocity_limit = self.velocity_limit;
+
+        (
+            &mut self.positions,
+            &mut self.previous_positions,
+            &mut self.velocities,
+            &mut self.ages,
+        )
+            .into_par_iter()
+            .for_each(|(position, previous_position, velocity, age)| {
+                if let Some(force) = field.sample(position) {
+                    *velocity = (*velocity + force).limit_magnitude(velocity_limit);
+                }
+
+                *previous_position = *position;
+                *position += *velocity;
+                *age += 1;
+            });
+    }
+
+    /// Moves the particles which have left the canvas to its opposite side, in parallel,
+    /// see [`super::Particle::wrap_around`].
+    pub fn wrap_around(&mut self, canvas: &Rect) {
+        (&mut self.positions, &mut self.previous_positions)
+            .into_par_iter()
+            .for_each(|(position, previous_position)| {
+                let wrapped = Point2::new(
+                    wrap(position.x, canvas.left(), canvas.right()),
+                    wrap(position.y, canvas.bottom(), canvas.top()),
+                );
+
+                if wrapped != *position {
+                    *position = wrapped;
+                    *previous_position = wrapped;
+                }
+            });
+    }
+
+    /// Draws the last step of every particle.
+    pub fn display(&self, draw: &Draw, color: Rgba8) {
+        for (previous_position, position) in self.previous_positions.iter().zip(&self.positions) {
+            draw.line()
+                .color(color)
+                .weight(1.0)
+                .points(*previous_position, *position);
+        }
+    }
+}
+
+fn wrap(value: f32, start: f32, end: f32) -> f32 {
+    if value > end {
+        start
+    } else if value < start {
+        end
+    } else {
+        value
+    }
+}
@@ -0,0 +1,469 @@
+//! Provides a particle system which keeps a population of particles alive.
+//!
+//! The system emits new particles from a set of [`Emitter`]s, steers them with a vector field,
+//! ages them and kills them once one of the [`DeathCondition`]s is met. What happens to a dead
+//! particle is decided by the system's [`Respawn`] policy.
+use super::{
+    boundary::{Boundary, BoundaryOutcome, Domain},
+    collision::{CollisionEvent, CollisionOutcome, Collisions},
+    coloring::ColorMapper,
+    physics::{FixedTimestep, Integrator, Physics},
+    Particle,
+};
+use crate::{
+    algorithms::{
+        flowfield::{obstacles::Obstacle, VectorField},
+        poisson_disc::PoissonDiscSampler,
+    },
+    rand::Samplable,
+};
+use nannou::{
+    color::Rgba8,
+    geom::{Point2, Rect, Vector2},
+    image::DynamicImage,
+    math::MetricSpace,
+    Draw,
+};
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use std::ops::RangeInclusive;
+
+/// Enumeration of the shapes from which particles can be emitted.
+pub enum Emitter {
+    /// Emits every particle at the same point.
+    Point(Point2),
+    /// Emits particles at uniformly distributed points of the segment between two points.
+    Line(Point2, Point2),
+    /// Emits particles at uniformly distributed points of a rect.
+    Rect(Rect),
+    /// Emits particles at randomly picked points of a set, see [`Emitter::poisson`].
+    Points(Vec<Point2>),
+    /// Emits particles at randomly picked cells of a grid laid over a canvas, each cell is
+    /// picked with a probability proportional to its weight, see [`Emitter::from_image`].
+    Weighted {
+        canvas: Rect,
+        columns: u32,
+        rows: u32,
+        weights: WeightedIndex<f32>,
+    },
+}
+
+impl Emitter {
+    /// Constructs an emitter from the points of a completed poisson-disc sampling of the domain.
+    pub fn poisson(domain: Rect, r: f32, k: u8) -> Self {
+        Emitter::Points(PoissonDiscSampler::sample_domain(domain, r, k))
+    }
+
+    /// Constructs an emitter which emits more particles from the brighter parts of a picture,
+    /// stretched over the canvas.
+    ///
+    /// Returns None if the picture is completely black.
+    pub fn from_image(picture: &DynamicImage, canvas: Rect) -> Option<Self> {
+        let luma = picture.to_luma8();
+        let (columns, rows) = luma.dimensions();
+
+        let weights = WeightedIndex::new(luma.pixels().map(|p| p[0] as f32)).ok()?;
+
+        Some(Emitter::Weighted {
+            canvas,
+            columns,
+            rows,
+            weights,
+        })
+    }
+
+    /// Checks whether the emitter has no positions to emit from, which is only the case for an
+    /// empty set of points.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Emitter::Points(points) => points.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Returns a new position from which a particle is emitted, or None if the emitter is
+    /// empty.
+    pub fn emit(&self) -> Option<Point2> {
+        let mut rng = rand::thread_rng();
+
+        let position = match self {
+            Emitter::Point(point) => *point,
+            Emitter::Line(start, end) => *start + (*end - *start) * rng.gen::<f32>(),
+            Emitter::Rect(rect) => Point2::random_from_domain(rect),
+            Emitter::Points(points) if points.is_empty() => return None,
+            Emitter::Points(points) => points[rng.gen_range(0..points.len())],
+            Emitter::Weighted {
+                canvas,
+                columns,
+                rows,
+                weights,
+            } => {
+                let index = weights.sample(&mut rng) as u32;
+                let (column, row) = (index % columns, index / columns);
+
+                let (w, h) = (canvas.w() / *columns as f32, canvas.h() / *rows as f32);
+
+                // The picture's rows start at the top, the canvas' y axis points up.
+                Point2::new(
+                    canvas.left() + (column as f32 + rng.gen::<f32>()) * w,
+                    canvas.top() - (row as f32 + rng.gen::<f32>()) * h,
+                )
+            }
+        };
+
+        Some(position)
+    }
+}
+
+/// Enumeration of the conditions under which a particle dies.
+pub enum DeathCondition {
+    /// The particle has reached its lifetime.
+    Expired,
+    /// The particle has left a rect.
+    OutsideRect(Rect),
+    /// The particle has left a circle.
+    OutsideCircle { center: Point2, radius: f32 },
+    /// The particle's speed has dropped below a threshold.
+    Stalled(f32),
+}
+
+impl DeathCondition {
+    pub fn is_met(&self, particle: &Particle) -> bool {
+        match self {
+            DeathCondition::Expired => particle.is_expired(),
+            DeathCondition::OutsideRect(rect) => !rect.contains(particle.position),
+            DeathCondition::OutsideCircle { center, radius } => {
+                particle.position.distance(*center) > *radius
+            }
+            DeathCondition::Stalled(speed) => particle.velocity().magnitude() < *speed,
+        }
+    }
+}
+
+/// Enumeration of the things which can happen to a dead particle.
+#[derive(Debug, Copy, Clone)]
+pub enum Respawn {
+    /// The particle is removed from the system.
+    Never,
+    /// The particle is replaced by a new one, emitted by one of the system's emitters.
+    /// A system without emitters removes its dead particles.
+    AtEmitter,
+}
+
+/// Encapsulates a population of particles together with their emitters.
+///
+/// Every update the system emits up to `emission_rate` new particles, as long as it holds
+/// less than `capacity` particles. Each particle gets a random lifetime from the lifetime range.
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    emitters: Vec<Emitter>,
+    capacity: usize,
+    emission_rate: usize,
+    lifetime: Option<RangeInclusive<u32>>,
+    death_conditions: Vec<DeathCondition>,
+    respawn: Respawn,
+    velocity_limit: f32,
+    color: Rgba8,
+    trail: Option<(usize, f32)>,
+    boundary: Option<(Domain, Boundary)>,
+    physics: Option<(Physics, Integrator, FixedTimestep)>,
+    color_mapper: Option<ColorMapper>,
+    collisions: Option<Collisions>,
+    collision_events: Vec<CollisionEvent>,
+}
+
+impl ParticleSystem {
+    /// Constructs a new, empty, instance of [`ParticleSystem`].
+    ///
+    /// Particles live forever until a lifetime and death conditions are set with
+    /// [`ParticleSystem::with_lifetime`] and [`ParticleSystem::with_death_condition`].
+    /// Empty emitters are left out, see [`Emitter::is_empty`].
+    pub fn new(
+        mut emitters: Vec<Emitter>,
+        capacity: usize,
+        emission_rate: usize,
+        velocity_limit: f32,
+        color: Rgba8,
+    ) -> Self {
+        emitters.retain(|emitter| !emitter.is_empty());
+
+        Self {
+            particles: Vec::with_capacity(capacity),
+            emitters,
+            capacity,
+            emission_rate,
+            lifetime: None,
+            death_conditions: vec![],
+            respawn: Respawn::AtEmitter,
+            velocity_limit,
+            color,
+            trail: None,
+            boundary: None,
+            physics: None,
+            color_mapper: None,
+            collisions: None,
+            collision_events: vec![],
+        }
+    }
+
+    /// Gives every particle emitted from now on a random lifetime from the range and makes
+    /// particles die once they reach it.
+    pub fn with_lifetime(mut self, lifetime: RangeInclusive<u32>) -> Self {
+        self.lifetime = Some(lifetime);
+
+        if !self
+            .death_conditions
+            .iter()
+            .any(|condition| matches!(condition, DeathCondition::Expired))
+        {
+            self.death_conditions.push(DeathCondition::Expired);
+        }
+
+        self
+    }
+
+    pub fn with_death_condition(mut self, death_condition: DeathCondition) -> Self {
+        self.death_conditions.push(death_condition);
+        self
+    }
+
+    pub fn with_respawn(mut self, respawn: Respawn) -> Self {
+        self.respawn = respawn;
+        self
+    }
+
+    /// Gives every particle emitted from now on a trail, see [`Particle::with_trail`].
+    pub fn with_trail(mut self, capacity: usize, weight: f32) -> Self {
+        self.trail = Some((capacity, weight));
+        self
+    }
+
+    /// Keeps the particles inside of a domain, particles killed by the boundary are handled
+    /// by the system's respawn policy.
+    pub fn with_boundary(mut self, domain: Domain, boundary: Boundary) -> Self {
+        self.boundary = Some((domain, boundary));
+        self
+    }
+
+    /// Makes [`ParticleSystem::simulate`] integrate the particles with a mass and drag,
+    /// in fixed steps of dt seconds, see [`Particle::integrate`].
+    ///
+    /// No more than max_steps steps are simulated per frame.
+    pub fn with_physics(
+        mut self,
+        physics: Physics,
+        integrator: Integrator,
+        dt: f32,
+        max_steps: u32,
+    ) -> Self {
+        self.physics = Some((physics, integrator, FixedTimestep::new(dt, max_steps)));
+        self
+    }
+
+    /// Recolors every particle with the mapper after it has moved.
+    pub fn with_color_mapper(mut self, color_mapper: ColorMapper) -> Self {
+        self.color_mapper = Some(color_mapper);
+        self
+    }
+
+    /// Makes the particles collide with obstacles, and possibly with each other.
+    ///
+    /// Particles which are killed by a collision are handled by the system's respawn policy.
+    /// Particles which stick become circular obstacles themselves and are then handled like
+    /// dead particles, so that the obstacles grow into clusters, as in diffusion-limited
+    /// aggregation.
+    pub fn with_collisions(mut self, collisions: Collisions) -> Self {
+        self.collisions = Some(collisions);
+        self
+    }
+
+    pub fn collisions(&self) -> Option<&Collisions> {
+        self.collisions.as_ref()
+    }
+
+    /// Returns the collisions which happened during the last update or simulation.
+    pub fn collision_events(&self) -> &[CollisionEvent] {
+        &self.collision_events
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn particles_mut(&mut self) -> &mut [Particle] {
+        &mut self.particles
+    }
+
+    /// Emits particles until the system holds as many as it can,
+    /// useful to start a sketch with a full population.
+    pub fn fill(&mut self) {
+        while self.particles.len() < self.capacity {
+            match self.emit() {
+                Some(particle) => self.particles.push(particle),
+                None => break,
+            }
+        }
+    }
+
+    /// Steers every particle with the vector field, updates it and handles the dead ones.
+    ///
+    /// Particles for which the field has no vector are not steered.
+    pub fn update<F>(&mut self, field: &F)
+    where
+        F: VectorField,
+    {
+        self.collision_events.clear();
+
+        self.step(|particle| {
+            if let Some(force) = field.sample(&particle.position) {
+                particle.apply_force(&force);
+            }
+
+            particle.update();
+        });
+    }
+
+    /// Integrates every particle over the time of a frame, in seconds, with the physics set by
+    /// [`ParticleSystem::with_physics`], and handles the dead ones after every step.
+    ///
+    /// The field is sampled as a force. A system without physics falls back to
+    /// [`ParticleSystem::update`], once per frame.
+    pub fn simulate<F>(&mut self, field: &F, frame_time: f32)
+    where
+        F: VectorField,
+    {
+        self.collision_events.clear();
+
+        let (physics, integrator, steps, dt) = match &mut self.physics {
+            None => return self.update(field),
+            Some((physics, integrator, timestep)) => (
+                *physics,
+                *integrator,
+                timestep.advance(frame_time),
+                timestep.dt(),
+            ),
+        };
+
+        for _ in 0..steps {
+            self.step(|particle| particle.integrate(field, &physics, integrator, dt));
+        }
+    }
+
+    pub fn display(&self, draw: &Draw) {
+        for particle in &self.particles {
+            particle.display(draw);
+        }
+    }
+
+    /// Moves every particle with the closure, applies the boundary and the color mapper,
+    /// handles the collisions and the dead particles and emits new ones.
+    fn step<M>(&mut self, mut movement: M)
+    where
+        M: FnMut(&mut Particle),
+    {
+        let mut killed = vec![false; self.particles.len()];
+
+        for (particle, killed) in self.particles.iter_mut().zip(killed.iter_mut()) {
+            movement(particle);
+
+            if let Some((domain, boundary)) = &self.boundary {
+                *killed = boundary.apply(particle, domain) == BoundaryOutcome::Killed;
+            }
+
+            if let Some(color_mapper) = &self.color_mapper {
+                color_mapper.apply(particle);
+            }
+        }
+
+        if let Some(collisions) = &mut self.collisions {
+            let events = &mut self.collision_events;
+            let outcomes = collisions.handle(&mut self.particles, |event| events.push(*event));
+
+            for (index, outcome) in outcomes.into_iter().enumerate() {
+                match outcome {
+                    CollisionOutcome::Killed => killed[index] = true,
+                    CollisionOutcome::Stuck => {
+                        collisions.add_obstacle(Obstacle::Circle {
+                            center: self.particles[index].position,
+                            radius: collisions.particle_radius(),
+                        });
+                        killed[index] = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut index = 0;
+
+        while index < self.particles.len() {
+            let is_dead = killed[index]
+                || self
+                    .death_conditions
+                    .iter()
+                    .any(|condition| condition.is_met(&self.particles[index]));
+
+            let replacement = match (is_dead, self.respawn) {
+                (false, _) => {
+                    index += 1;
+                    continue;
+                }
+                (true, Respawn::AtEmitter) => self.emit(),
+                (true, Respawn::Never) => None,
+            };
+
+            match replacement {
+                Some(particle) => {
+                    self.particles[index] = particle;
+                    index += 1;
+                }
+                // The flags are removed alike, so that they stay aligned with the particles.
+                None => {
+                    self.particles.swap_remove(index);
+                    killed.swap_remove(index);
+                }
+            }
+        }
+
+        for _ in 0..self.emission_rate {
+            if self.particles.len() >= self.capacity {
+                break;
+            }
+
+            match self.emit() {
+                Some(particle) => self.particles.push(particle),
+                None => break,
+            }
+        }
+    }
+
+    /// Creates a new particle at a position given by a randomly picked emitter, or None if the
+    /// system has no emitters.
+    fn emit(&self) -> Option<Particle> {
+        let mut rng = rand::thread_rng();
+
+        if self.emitters.is_empty() {
+            return None;
+        }
+
+        let emitter = &self.emitters[rng.gen_range(0..self.emitters.len())];
+
+        let mut particle = Particle::new(
+            emitter.emit()?,
+            None,
+            Vector2::zero(),
+            Vector2::random_from_magnitude_range(1.0..=1.0),
+            self.velocity_limit,
+            self.color,
+        );
+
+        particle.lifetime = self
+            .lifetime
+            .as_ref()
+            .map(|lifetime| rng.gen_range(lifetime.clone()));
+
+        match self.trail {
+            None => Some(particle),
+            Some((capacity, weight)) => Some(particle.with_trail(capacity, weight)),
+        }
+    }
+}
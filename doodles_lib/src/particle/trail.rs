@@ -0,0 +1,72 @@
+//! Provides a bounded history of a particle's positions, which is drawn as a tapering polyline.
+use nannou::{
+    color::{rgba8, Rgba8},
+    geom::Point2,
+    Draw,
+};
+use std::collections::VecDeque;
+
+/// Holds the most recent positions of a particle, oldest first.
+///
+/// Once the trail holds as many positions as its capacity, every new position pushes out
+/// the oldest one. A trail always holds at least the newest position.
+#[derive(Debug, Clone)]
+pub struct Trail {
+    points: VecDeque<Point2>,
+    capacity: usize,
+    weight: f32,
+}
+
+impl Trail {
+    /// Constructs a new, empty, instance of [`Trail`].
+    ///
+    /// The weight is the width of the trail's newest segment.
+    pub fn new(capacity: usize, weight: f32) -> Self {
+        Self {
+            points: VecDeque::with_capacity(capacity),
+            capacity,
+            weight,
+        }
+    }
+
+    pub fn push(&mut self, point: Point2) {
+        while self.points.len() >= self.capacity.max(1) {
+            self.points.pop_front();
+        }
+
+        self.points.push_back(point);
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &Point2> {
+        self.points.iter()
+    }
+
+    /// Draws the trail as a polyline whose width and alpha taper off linearly,
+    /// from the full weight and color at the newest position towards nothing at the oldest one.
+    pub fn display(&self, draw: &Draw, color: Rgba8) {
+        let segments = self.points.len().saturating_sub(1);
+
+        for (index, (start, end)) in self
+            .points
+            .iter()
+            .zip(self.points.iter().skip(1))
+            .enumerate()
+        {
+            let t = (index + 1) as f32 / segments as f32;
+
+            draw.line()
+                .color(rgba8(
+                    color.red,
+                    color.green,
+                    color.blue,
+                    (color.alpha as f32 * t) as u8,
+                ))
+                .weight(self.weight * t)
+                .points(*start, *end);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Implementation of the adjacency graph of a tiling.
+//!
+//! Two tiles are adjacent if they share a piece of an edge, touching at a corner isn't enough.
+//! Adjacency is found for rectangular tiles, see [`AdjacencyGraph::from_rects`], including the
+//! partial overlaps of edges in domino tilings, where the long edge of one tile meets the short
+//! edges of two others.
+use super::Rectangular;
+use crate::collections::SpatialHash;
+use nannou::geom::Rect;
+use std::collections::VecDeque;
+
+/// An edge of the graph, leading to a neighbouring tile.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Adjacency {
+    /// The index of the neighbouring tile.
+    pub neighbour: usize,
+    /// The length of the segment which both tiles share.
+    pub length: f32,
+}
+
+/// The graph whose nodes are the tiles of a tiling, in the order of the tiling, and whose edges
+/// connect adjacent tiles.
+#[derive(Debug, Clone)]
+pub struct AdjacencyGraph {
+    adjacencies: Vec<Vec<Adjacency>>,
+}
+
+impl AdjacencyGraph {
+    /// Constructs the graph of rectangular tiles.
+    ///
+    /// Edges are matched with a tolerance relative to the size of the smallest tile, so that
+    /// the rounding errors of deep subdivisions don't disconnect the tiles.
+    pub fn from_rects<T>(tiles: &[T]) -> Self
+    where
+        T: Rectangular,
+    {
+        let mut adjacencies = vec![vec![]; tiles.len()];
+
+        if tiles.is_empty() {
+            return Self { adjacencies };
+        }
+
+        let sizes = tiles
+            .iter()
+            .map(|tile| tile.rect().w().max(tile.rect().h()));
+        let cell_size = sizes.clone().sum::<f32>() / tiles.len() as f32;
+        let tolerance = sizes.fold(f32::MAX, f32::min) * 1e-4;
+
+        let mut spatial_hash = SpatialHash::new(cell_size);
+
+        for (index, tile) in tiles.iter().enumerate() {
+            spatial_hash.insert_rect(index, &tile.rect().pad(-tolerance));
+        }
+
+        for (index, tile) in tiles.iter().enumerate() {
+            let rect = tile.rect();
+            let radius = rect.w().max(rect.h()) / 2.0 + tolerance;
+
+            let mut candidates: Vec<usize> = spatial_hash
+                .query(&rect.xy(), radius)
+                .filter(|&other| other > index)
+                .collect();
+
+            candidates.sort_unstable();
+            candidates.dedup();
+
+            for other in candidates {
+                if let Some(length) = shared_length(rect, tiles[other].rect(), tolerance) {
+                    adjacencies[index].push(Adjacency {
+                        neighbour: other,
+                        length,
+                    });
+                    adjacencies[other].push(Adjacency {
+                        neighbour: index,
+                        length,
+                    });
+                }
+            }
+        }
+
+        Self { adjacencies }
+    }
+
+    /// Returns the number of tiles.
+    pub fn len(&self) -> usize {
+        self.adjacencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adjacencies.is_empty()
+    }
+
+    pub fn neighbours(&self, index: usize) -> &[Adjacency] {
+        &self.adjacencies[index]
+    }
+
+    /// Returns every edge once, as the indices of both tiles and the length of their shared
+    /// segment.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, f32)> + '_ {
+        self.adjacencies
+            .iter()
+            .enumerate()
+            .flat_map(|(index, adjacencies)| {
+                adjacencies
+                    .iter()
+                    .filter(move |adjacency| adjacency.neighbour > index)
+                    .map(move |adjacency| (index, adjacency.neighbour, adjacency.length))
+            })
+    }
+
+    /// Assigns a colour to every tile, so that adjacent tiles have different colours.
+    ///
+    /// Colours are numbered from 0. Tiles are coloured greedily, those with the most neighbours
+    /// first, which usually needs few colours but isn't guaranteed to find the fewest.
+    pub fn coloring(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by_key(|&index| std::cmp::Reverse(self.adjacencies[index].len()));
+
+        let mut colors: Vec<Option<usize>> = vec![None; self.len()];
+
+        for index in order {
+            let used: Vec<usize> = self.adjacencies[index]
+                .iter()
+                .filter_map(|adjacency| colors[adjacency.neighbour])
+                .collect();
+
+            colors[index] = (0..).find(|color| !used.contains(color));
+        }
+
+        colors.into_iter().flatten().collect()
+    }
+
+    /// Returns the number of edges on the shortest path from the start to every tile, or None
+    /// for tiles which can't be reached.
+    pub fn distances(&self, start: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        let mut queue = VecDeque::new();
+
+        distances[start] = Some(0);
+        queue.push_back(start);
+
+        while let Some(index) = queue.pop_front() {
+            let distance = distances[index].map(|distance| distance + 1);
+
+            for adjacency in &self.adjacencies[index] {
+                if distances[adjacency.neighbour].is_none() {
+                    distances[adjacency.neighbour] = distance;
+                    queue.push_back(adjacency.neighbour);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Returns the groups of tiles which are connected by edges, each sorted by index.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut is_visited = vec![false; self.len()];
+        let mut components = vec![];
+
+        for start in 0..self.len() {
+            if is_visited[start] {
+                continue;
+            }
+
+            is_visited[start] = true;
+
+            let mut component = vec![start];
+            let mut stack = vec![start];
+
+            while let Some(index) = stack.pop() {
+                for adjacency in &self.adjacencies[index] {
+                    if !is_visited[adjacency.neighbour] {
+                        is_visited[adjacency.neighbour] = true;
+                        component.push(adjacency.neighbour);
+                        stack.push(adjacency.neighbour);
+                    }
+                }
+            }
+
+            component.sort_unstable();
+            components.push(component);
+        }
+
+        components
+    }
+}
+
+/// Returns the length of the segment which the edges of both rects share, or None if they
+/// don't touch, or only touch at a corner.
+fn shared_length(a: &Rect, b: &Rect, tolerance: f32) -> Option<f32> {
+    let overlap = |start_a: f32, end_a: f32, start_b: f32, end_b: f32| {
+        end_a.min(end_b) - start_a.max(start_b)
+    };
+
+    let length = if (a.right() - b.left()).abs() < tolerance
+        || (b.right() - a.left()).abs() < tolerance
+    {
+        overlap(a.bottom(), a.top(), b.bottom(), b.top())
+    } else if (a.top() - b.bottom()).abs() < tolerance || (b.top() - a.bottom()).abs() < tolerance {
+        overlap(a.left(), a.right(), b.left(), b.right())
+    } else {
+        return None;
+    };
+
+    match length > tolerance {
+        true => Some(length),
+        false => None,
+    }
+}
@@ -3,8 +3,8 @@
 //! This tiling divides two proto-tiles, a horizontal and a vertical one, in four.
 //! The subdivision process varies for each type of tile, the difference being in the division
 //! proportions and the placing of its children.
-use super::{Divisible, Rectangular};
-use nannou::geom::Rect;
+use super::{polygon::rect_vertices, Divisible, Labelled, Polygonal, Rectangular};
+use nannou::geom::{Point2, Rect};
 
 /// Enumeration of the tile variants used to construct a domino tiling.
 ///
@@ -52,3 +52,18 @@ impl Rectangular for DominoTile {
         }
     }
 }
+
+impl Polygonal for DominoTile {
+    fn vertices(&self) -> Vec<Point2> {
+        rect_vertices(self.rect())
+    }
+}
+
+impl Labelled for DominoTile {
+    fn label(&self) -> &str {
+        match self {
+            DominoTile::Horizontal(_) => "horizontal",
+            DominoTile::Vertical(_) => "vertical",
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Implementation of a tiling which keeps the whole history of its subdivision.
+//!
+//! [`super::create_tiling`] only returns the tiles of the last step. A [`TilingTree`] keeps the
+//! tiles of every step in an arena, linked to their parents and children, so that tiles can be
+//! coloured by their ancestors, supertiles can be drawn at several levels at once and the
+//! subdivision can be animated one step at a time.
+use super::{Divisible, Labelled};
+use std::ops::Range;
+
+/// A tile of a tiling tree and its place in the hierarchy.
+#[derive(Debug, Clone)]
+pub struct TilingNode<T> {
+    pub tile: T,
+    /// The number of divisions which produced the tile, 0 for the initial tiles.
+    pub depth: u8,
+    /// The index of the tile which was divided into this one, None for the initial tiles.
+    pub parent: Option<usize>,
+    /// The position of the tile among its siblings, or among the initial tiles.
+    pub child_index: usize,
+    /// The indices of the tiles which this one was divided into, empty for the leaves.
+    pub children: Range<usize>,
+}
+
+impl<T> TilingNode<T>
+where
+    T: Labelled,
+{
+    /// Returns the label of the tile's proto-tile.
+    pub fn label(&self) -> &str {
+        self.tile.label()
+    }
+}
+
+/// An arena which holds every tile of every step of a tiling.
+///
+/// Nodes are stored level by level, every level in the order in which [`Divisible::divide`]
+/// returned the tiles, so the children of every node are next to each other.
+#[derive(Debug, Clone)]
+pub struct TilingTree<T> {
+    nodes: Vec<TilingNode<T>>,
+    levels: Vec<Range<usize>>,
+}
+
+impl<T> TilingTree<T> {
+    pub fn nodes(&self) -> &[TilingNode<T>] {
+        &self.nodes
+    }
+
+    pub fn node(&self, index: usize) -> &TilingNode<T> {
+        &self.nodes[index]
+    }
+
+    /// Returns the number of levels, which is one more than the number of steps taken.
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Returns the nodes at the given depth, the tiles of the tiling after that many steps.
+    pub fn level(&self, depth: u8) -> &[TilingNode<T>] {
+        &self.nodes[self.levels[depth as usize].clone()]
+    }
+
+    /// Returns the nodes which weren't divided any further, the tiles of the completed tiling.
+    pub fn leaves(&self) -> impl Iterator<Item = &TilingNode<T>> {
+        self.nodes.iter().filter(|node| node.children.is_empty())
+    }
+
+    pub fn parent(&self, index: usize) -> Option<&TilingNode<T>> {
+        self.nodes[index].parent.map(|parent| &self.nodes[parent])
+    }
+
+    pub fn children(&self, index: usize) -> &[TilingNode<T>] {
+        &self.nodes[self.nodes[index].children.clone()]
+    }
+
+    /// Returns the ancestor of the node at the given depth, or the node itself if it is at that
+    /// depth. Returns None if the node is above the given depth.
+    pub fn ancestor(&self, index: usize, depth: u8) -> Option<&TilingNode<T>> {
+        let mut node = &self.nodes[index];
+
+        while node.depth > depth {
+            node = &self.nodes[node.parent?];
+        }
+
+        match node.depth == depth {
+            true => Some(node),
+            false => None,
+        }
+    }
+}
+
+/// Create a tiling just like [`super::create_tiling`], but keep the tiles of every step in a
+/// [`TilingTree`].
+///
+/// The tree has steps + 1 levels, the first one holding the initial tiles.
+pub fn create_tiling_tree<T>(tiles: Vec<T>, steps: u8) -> TilingTree<T>
+where
+    T: Divisible,
+{
+    let mut nodes: Vec<TilingNode<T>> = tiles
+        .into_iter()
+        .enumerate()
+        .map(|(child_index, tile)| TilingNode {
+            tile,
+            depth: 0,
+            parent: None,
+            child_index,
+            children: 0..0,
+        })
+        .collect();
+
+    let mut levels = Vec::with_capacity(steps as usize + 1);
+    levels.push(0..nodes.len());
+
+    for depth in 1..=steps {
+        let start = nodes.len();
+
+        for parent in levels[levels.len() - 1].clone() {
+            let children = nodes[parent].tile.divide();
+            let first_child = nodes.len();
+
+            nodes.extend(
+                children
+                    .into_iter()
+                    .enumerate()
+                    .map(|(child_index, tile)| TilingNode {
+                        tile,
+                        depth,
+                        parent: Some(parent),
+                        child_index,
+                        children: 0..0,
+                    }),
+            );
+
+            nodes[parent].children = first_child..nodes.len();
+        }
+
+        levels.push(start..nodes.len());
+    }
+
+    TilingTree { nodes, levels }
+}
@@ -3,9 +3,22 @@
 //! In mathematics a tiling is a collection of geometric shapes (called tiles) which cover
 //! the plane without any gaps or overlaps. Each tile of a tilling must be a topological disc,
 //! meaning it must be a connected piece without any holes or lines.
-use nannou::geom::Rect;
+//!
+//! Tiles are either axis-aligned rects, see [`Rectangular`], or arbitrary polygons, see
+//! [`Polygonal`]. Every rectangular tile is polygonal as well.
+use crate::geometry::polygon as geometry;
+use nannou::geom::{Point2, Rect};
 
+pub mod adjacency;
 pub mod domino;
+pub mod hierarchy;
+pub mod monotile;
+pub mod penrose;
+pub mod pinwheel;
+pub mod polygon;
+pub mod substitution;
+pub mod verification;
+pub mod viewport;
 pub mod wanderer;
 
 /// This Divisible trait facilitates the division of a tile.
@@ -16,33 +29,96 @@ pub trait Divisible {
     fn divide(&self) -> Vec<Self>
     where
         Self: Sized;
+
+    /// Checks whether the tile can't be divided any further, like the monotiles of [`monotile`].
+    ///
+    /// Tilings stop dividing leaves, which keep their place among the tiles.
+    fn is_leaf(&self) -> bool {
+        false
+    }
 }
 
 pub trait Rectangular {
     fn rect(&self) -> &Rect;
 }
 
+/// This Polygonal trait describes tiles which are simple polygons.
+///
+/// Each polygonal tile must implement the vertices method, which returns its vertices in the
+/// plane, the geometric helpers are derived from them.
+pub trait Polygonal {
+    fn vertices(&self) -> Vec<Point2>;
+
+    fn area(&self) -> f32 {
+        geometry::signed_area(&self.vertices()).abs()
+    }
+
+    fn centroid(&self) -> Point2 {
+        geometry::centroid(&self.vertices())
+    }
+
+    fn contains(&self, point: &Point2) -> bool {
+        geometry::contains(&self.vertices(), point)
+    }
+
+    fn bounds(&self) -> Rect {
+        geometry::bounds(&self.vertices())
+    }
+}
+
+/// This Labelled trait names the proto-tile which a tile is a copy of.
+///
+/// Labels tell apart the kinds of tiles of a tiling, e.g. the horizontal and vertical tiles of a
+/// domino tiling, and are in lowercase with words separated by dashes.
+pub trait Labelled {
+    fn label(&self) -> &str;
+}
+
 /// Create a tiling based on the type of element that the input vec holds.
 ///
 /// To create a tiling one must supply a vec holding the initial tiles (usually just one)
 /// and an [`u8`] representing how many steps the tiling algorithm should take.
 /// Based on the type that the vec holds, an appropriate algorithm will be executed and a new vec,
 /// holding the tiles of the completed tiling, will be returned.
-pub fn create_tiling<T>(mut tiles: Vec<T>, mut steps: u8) -> Vec<T>
+/// Rectangular and polygonal tiles are created alike, the algorithm only relies on [`Divisible`].
+/// If steps is 0 the initial tiles are returned as they are.
+pub fn create_tiling<T>(tiles: Vec<T>, steps: u8) -> Vec<T>
 where
     T: Divisible,
 {
-    steps -= 1;
-
-    let divided_tiles: Vec<T> = tiles
-        .drain(..)
-        .map(|tile| tile.divide())
-        .flatten()
-        .collect();
-
-    if steps == 0 {
-        divided_tiles
-    } else {
-        create_tiling(divided_tiles, steps)
+    create_adaptive_tiling(tiles, |_, depth| depth < steps)
+}
+
+/// Create a tiling whose tiles are divided for as long as a predicate holds.
+///
+/// The predicate is called with every tile and the number of divisions which produced it, 0 for
+/// the initial tiles, and decides whether the tile is divided any further. Tiles can thus be
+/// divided e.g. only near a point, depending on noise or the detail of an image, or at random
+/// with a probability per depth, which creates tilings of mixed scales. Leaves aren't divided,
+/// see [`Divisible::is_leaf`], and no tile is divided more than [`u8::MAX`] times.
+///
+/// The tiles of the completed tiling are returned in the same order as by [`create_tiling`],
+/// the descendants of every tile are next to each other.
+pub fn create_adaptive_tiling<T, F>(tiles: Vec<T>, mut should_divide: F) -> Vec<T>
+where
+    T: Divisible,
+    F: FnMut(&T, u8) -> bool,
+{
+    let mut stack: Vec<(T, u8)> = tiles.into_iter().rev().map(|tile| (tile, 0)).collect();
+    let mut tiling = vec![];
+
+    while let Some((tile, depth)) = stack.pop() {
+        if depth < u8::MAX && !tile.is_leaf() && should_divide(&tile, depth) {
+            stack.extend(
+                tile.divide()
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, depth + 1)),
+            );
+        } else {
+            tiling.push(tile);
+        }
     }
+
+    tiling
 }
@@ -0,0 +1,729 @@
+//! Implementation of the hat and Spectre tilings, built from the aperiodic monotiles discovered
+//! in 2023.
+//!
+//! Both tilings are created by the substitution systems published along with the monotiles.
+//! Hats are grouped into the four metatiles H, T, P and F, which are in turn grouped into
+//! larger supertiles of the same four types, and so on. Spectres are grouped into nine clusters,
+//! Γ (a pair of spectres, the mystic) and Δ, Θ, Λ, Ξ, Π, Σ, Φ and Ψ (single spectres), which are
+//! grouped into larger clusters of the same nine types.
+//!
+//! The supertiles of every level are computed once, when the tiling is seeded, see
+//! [`MonotileTile::seed`]. Every division then replaces a supertile with the supertiles of the
+//! level below, until only monotiles are left. [`MonotileTile::patch`] does both at once.
+use super::{
+    create_tiling,
+    polygon::{Affine, Polygon},
+    Divisible, Labelled, Polygonal,
+};
+use crate::geometry::polygon as geometry;
+use nannou::geom::{pt2, Point2};
+use std::{collections::HashMap, f32::consts::PI, rc::Rc};
+
+const HALF_SQRT_3: f32 = 0.866_025_4;
+
+/// Enumeration of the two aperiodic monotiles.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Monotile {
+    /// The hat, a polykite which tiles the plane only aperiodically, together with its mirror
+    /// image.
+    Hat,
+    /// The Spectre, which tiles the plane only aperiodically without its mirror image.
+    Spectre,
+}
+
+/// Enumeration of the metatiles of the hat tiling and the clusters of the Spectre tiling.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Metatile {
+    H,
+    T,
+    P,
+    F,
+    Gamma,
+    Delta,
+    Theta,
+    Lambda,
+    Xi,
+    Pi,
+    Sigma,
+    Phi,
+    Psi,
+}
+
+/// The shape of a supertile and the placements of its children, relative to it.
+#[derive(Debug)]
+struct Definition {
+    outline: Vec<Point2>,
+    /// The children are supertiles of the level below, or monotiles for supertiles of level 0,
+    /// which are labelled with the type of their parent.
+    children: Vec<(Affine, Metatile)>,
+}
+
+/// The supertiles of every level of a tiling.
+#[derive(Debug)]
+struct Substitution {
+    monotile: Monotile,
+    levels: Vec<HashMap<Metatile, Definition>>,
+    /// The centers of the supertiles of every level, the average of their children's centers.
+    centers: Vec<HashMap<Metatile, Point2>>,
+}
+
+impl Substitution {
+    fn new(monotile: Monotile, levels: usize) -> Self {
+        let mut substitution = Self {
+            monotile,
+            levels: vec![match monotile {
+                Monotile::Hat => hat_metatiles(),
+                Monotile::Spectre => spectre_clusters(),
+            }],
+            centers: vec![],
+        };
+
+        while substitution.levels.len() < levels {
+            let previous = &substitution.levels[substitution.levels.len() - 1];
+            let next = match monotile {
+                Monotile::Hat => hat_supertiles(previous),
+                Monotile::Spectre => spectre_supertiles(previous),
+            };
+
+            substitution.levels.push(next);
+        }
+
+        let monotile_center = geometry::centroid(&monotile_outline(monotile));
+
+        for (level, definitions) in substitution.levels.iter().enumerate() {
+            let centers = definitions
+                .iter()
+                .map(|(metatile, definition)| {
+                    let sum = definition.children.iter().fold(
+                        pt2(0.0, 0.0),
+                        |sum, (transform, child)| {
+                            let center = match level {
+                                0 => monotile_center,
+                                _ => substitution.centers[level - 1][child],
+                            };
+
+                            sum + transform.apply(&center)
+                        },
+                    );
+
+                    (*metatile, sum / definition.children.len() as f32)
+                })
+                .collect();
+
+            substitution.centers.push(centers);
+        }
+
+        substitution
+    }
+
+    fn definition(&self, metatile: Metatile, level: usize) -> &Definition {
+        &self.levels[level][&metatile]
+    }
+}
+
+/// A tile of a hat or Spectre tiling, either a supertile or a single monotile.
+///
+/// Supertiles are polygons with the outline of the metatile, or with the quadrilateral spanned
+/// by the four key points of the cluster for the Spectre tiling. Monotiles keep the label of the
+/// metatile they belong to, and might be mirror images of the monotile, see
+/// [`MonotileTile::is_reflected`].
+#[derive(Debug, Clone)]
+pub struct MonotileTile {
+    substitution: Rc<Substitution>,
+    metatile: Metatile,
+    level: Option<usize>,
+    polygon: Polygon,
+}
+
+impl MonotileTile {
+    /// Creates a patch of monotiles, seeded and divided with the same number of steps, at least
+    /// one, see [`MonotileTile::seed`].
+    pub fn patch(monotile: Monotile, steps: u8, center: Point2, edge_length: f32) -> Vec<Self> {
+        let steps = steps.max(1);
+
+        create_tiling(Self::seed(monotile, steps, center, edge_length), steps)
+    }
+
+    /// Creates the supertile which turns into a patch of monotiles after the given number of
+    /// steps of [`super::create_tiling`].
+    ///
+    /// The supertile is centered at center and its monotiles have edges of edge_length, so the
+    /// patch grows with the number of steps. The tiling must be created with the same number of
+    /// steps: with fewer steps some supertiles are left, see [`MonotileTile::level`], with more
+    /// steps the monotiles stay as they are, since they are leaves.
+    /// [`MonotileTile::patch`] takes care of both.
+    pub fn seed(monotile: Monotile, steps: u8, center: Point2, edge_length: f32) -> Vec<Self> {
+        let level = (steps as usize).saturating_sub(1);
+        let substitution = Rc::new(Substitution::new(monotile, level + 1));
+
+        let (metatile, scale) = match monotile {
+            // The metatiles place hats at half their size, which makes their short edges 0.5 long.
+            Monotile::Hat => (Metatile::H, edge_length * 2.0),
+            Monotile::Spectre => (Metatile::Delta, edge_length),
+        };
+
+        // The spectres of every other level of clusters are mirrored, so that all spectres of the
+        // patch face the same way, the seed is mirrored as well if necessary.
+        let mirror = match (monotile, level % 2) {
+            (Monotile::Spectre, 1) => -1.0,
+            _ => 1.0,
+        };
+
+        let outline = &substitution.definition(metatile, level).outline;
+        let centroid = substitution.centers[level][&metatile];
+
+        let transform = Affine::translation(-centroid.x, -centroid.y)
+            .then(&Affine::scale(mirror * scale, scale))
+            .then(&Affine::translation(center.x, center.y));
+
+        vec![MonotileTile {
+            polygon: Polygon::new(outline.clone(), transform),
+            substitution,
+            metatile,
+            level: Some(level),
+        }]
+    }
+
+    pub fn monotile(&self) -> Monotile {
+        self.substitution.monotile
+    }
+
+    /// Returns the type of the supertile, or of the metatile which the monotile belongs to.
+    pub fn metatile(&self) -> Metatile {
+        self.metatile
+    }
+
+    /// Returns the level of the supertile, or None for a monotile.
+    pub fn level(&self) -> Option<usize> {
+        self.level
+    }
+
+    pub fn is_monotile(&self) -> bool {
+        self.level.is_none()
+    }
+
+    /// Checks whether the tile is a mirror image, which in the hat tiling is the case for
+    /// roughly one in seven hats. Spectres are never mirrored.
+    pub fn is_reflected(&self) -> bool {
+        self.polygon.transform().is_reflection()
+    }
+
+    pub fn polygon(&self) -> &Polygon {
+        &self.polygon
+    }
+}
+
+/// Monotiles are leaves, dividing one returns it unchanged.
+impl Divisible for MonotileTile {
+    fn divide(&self) -> Vec<Self> {
+        let level = match self.level {
+            Some(level) => level,
+            None => return vec![self.clone()],
+        };
+
+        let definition = self.substitution.definition(self.metatile, level);
+
+        definition
+            .children
+            .iter()
+            .map(|(relative, metatile)| {
+                let prototile = match level {
+                    0 => monotile_outline(self.substitution.monotile),
+                    _ => {
+                        let child = self.substitution.definition(*metatile, level - 1);
+                        child.outline.clone()
+                    }
+                };
+
+                MonotileTile {
+                    substitution: self.substitution.clone(),
+                    metatile: *metatile,
+                    level: level.checked_sub(1),
+                    polygon: self.polygon.place(prototile, relative),
+                }
+            })
+            .collect()
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.is_monotile()
+    }
+}
+
+impl Polygonal for MonotileTile {
+    fn vertices(&self) -> Vec<Point2> {
+        self.polygon.vertices()
+    }
+}
+
+/// Monotiles are labelled `hat` or `spectre`, supertiles by their metatile, e.g. `h` or `gamma`.
+impl Labelled for MonotileTile {
+    fn label(&self) -> &str {
+        match (self.level, self.monotile()) {
+            (None, Monotile::Hat) => "hat",
+            (None, Monotile::Spectre) => "spectre",
+            (Some(_), _) => match self.metatile {
+                Metatile::H => "h",
+                Metatile::T => "t",
+                Metatile::P => "p",
+                Metatile::F => "f",
+                Metatile::Gamma => "gamma",
+                Metatile::Delta => "delta",
+                Metatile::Theta => "theta",
+                Metatile::Lambda => "lambda",
+                Metatile::Xi => "xi",
+                Metatile::Pi => "pi",
+                Metatile::Sigma => "sigma",
+                Metatile::Phi => "phi",
+                Metatile::Psi => "psi",
+            },
+        }
+    }
+}
+
+fn monotile_outline(monotile: Monotile) -> Vec<Point2> {
+    match monotile {
+        Monotile::Hat => hat_outline(),
+        Monotile::Spectre => spectre_outline(),
+    }
+}
+
+/// Returns a point of the hexagonal grid, given in coordinates along the axes at 0 and 60
+/// degrees.
+fn hex_point(x: f32, y: f32) -> Point2 {
+    pt2(x + 0.5 * y, HALF_SQRT_3 * y)
+}
+
+fn hat_outline() -> Vec<Point2> {
+    [
+        (0.0, 0.0),
+        (-1.0, -1.0),
+        (0.0, -2.0),
+        (2.0, -2.0),
+        (2.0, -1.0),
+        (4.0, -2.0),
+        (5.0, -1.0),
+        (4.0, 0.0),
+        (3.0, 0.0),
+        (2.0, 2.0),
+        (0.0, 3.0),
+        (0.0, 2.0),
+        (-1.0, 2.0),
+    ]
+    .iter()
+    .map(|&(x, y)| hex_point(x, y))
+    .collect()
+}
+
+/// Returns the outline of the Spectre with straight edges, i.e. the equilateral polygon whose
+/// edges have length 1. Two of its edges are collinear, they are curved in opposite directions
+/// in the proper Spectre.
+fn spectre_outline() -> Vec<Point2> {
+    let (a, b) = (HALF_SQRT_3, 0.5);
+
+    vec![
+        pt2(0.0, 0.0),
+        pt2(1.0, 0.0),
+        pt2(1.0 + b, -a),
+        pt2(1.0 + b + a, -a + b),
+        pt2(1.0 + b + a, -a + 1.0 + b),
+        pt2(2.0 + b + a, -a + 1.0 + b),
+        pt2(3.0 + a, 1.0 + b),
+        pt2(3.0, 2.0),
+        pt2(3.0 - a, 2.0 - b),
+        pt2(2.0 + b - a, a + 2.0 - b),
+        pt2(1.0 + b - a, a + 2.0 - b),
+        pt2(b - a, a + 2.0 - b),
+        pt2(-a, 2.0 - b),
+        pt2(0.0, 1.0),
+    ]
+}
+
+/// Returns the metatiles of the hat tiling, made of hats.
+fn hat_metatiles() -> HashMap<Metatile, Definition> {
+    let hat = hat_outline();
+    let half = Affine::scale(0.5, 0.5);
+    // Places a hat half the size, rotated by 60 degrees clockwise, at the top left of the
+    // P and F metatiles.
+    let rotated = half
+        .then(&Affine::rotation(-PI / 3.0))
+        .then(&Affine::translation(0.0, 2.0 * HALF_SQRT_3));
+
+    let h_outline = vec![
+        pt2(0.0, 0.0),
+        pt2(4.0, 0.0),
+        pt2(4.5, HALF_SQRT_3),
+        pt2(2.5, 5.0 * HALF_SQRT_3),
+        pt2(1.5, 5.0 * HALF_SQRT_3),
+        pt2(-0.5, HALF_SQRT_3),
+    ];
+
+    let h_children = vec![
+        (
+            match_segments(hat[5], hat[7], h_outline[5], h_outline[0]),
+            Metatile::H,
+        ),
+        (
+            match_segments(hat[9], hat[11], h_outline[1], h_outline[2]),
+            Metatile::H,
+        ),
+        (
+            match_segments(hat[5], hat[7], h_outline[3], h_outline[4]),
+            Metatile::H,
+        ),
+        // The only reflected hat.
+        (
+            Affine::new(0.5, 0.0, 0.0, 0.0, -0.5, 0.0)
+                .then(&Affine::rotation(2.0 * PI / 3.0))
+                .then(&Affine::translation(2.5, HALF_SQRT_3)),
+            Metatile::H,
+        ),
+    ];
+
+    let mut metatiles = HashMap::new();
+
+    metatiles.insert(
+        Metatile::H,
+        Definition {
+            outline: h_outline,
+            children: h_children,
+        },
+    );
+    metatiles.insert(
+        Metatile::T,
+        Definition {
+            outline: vec![pt2(0.0, 0.0), pt2(3.0, 0.0), pt2(1.5, 3.0 * HALF_SQRT_3)],
+            children: vec![(
+                half.then(&Affine::translation(0.5, HALF_SQRT_3)),
+                Metatile::T,
+            )],
+        },
+    );
+    metatiles.insert(
+        Metatile::P,
+        Definition {
+            outline: vec![
+                pt2(0.0, 0.0),
+                pt2(4.0, 0.0),
+                pt2(3.0, 2.0 * HALF_SQRT_3),
+                pt2(-1.0, 2.0 * HALF_SQRT_3),
+            ],
+            children: vec![
+                (
+                    half.then(&Affine::translation(1.5, HALF_SQRT_3)),
+                    Metatile::P,
+                ),
+                (rotated, Metatile::P),
+            ],
+        },
+    );
+    metatiles.insert(
+        Metatile::F,
+        Definition {
+            outline: vec![
+                pt2(0.0, 0.0),
+                pt2(3.0, 0.0),
+                pt2(3.5, HALF_SQRT_3),
+                pt2(3.0, 2.0 * HALF_SQRT_3),
+                pt2(-1.0, 2.0 * HALF_SQRT_3),
+            ],
+            children: vec![
+                (
+                    half.then(&Affine::translation(1.5, HALF_SQRT_3)),
+                    Metatile::F,
+                ),
+                (rotated, Metatile::F),
+            ],
+        },
+    );
+
+    metatiles
+}
+
+/// Enumeration of the rules which place the metatiles of a patch, from which the supertiles of
+/// the hat tiling are cut.
+enum PatchRule {
+    /// Places the metatile so that its edge, starting at the vertex, is glued to the edge of
+    /// an already placed metatile, starting at the vertex.
+    Edge(usize, usize, Metatile, usize),
+    /// Places the metatile so that its edge, starting at the vertex, runs between a vertex of
+    /// each of two already placed metatiles.
+    Vertices(usize, usize, usize, usize, Metatile, usize),
+}
+
+/// Returns the supertiles of the hat tiling, made of the metatiles of the level below.
+fn hat_supertiles(metatiles: &HashMap<Metatile, Definition>) -> HashMap<Metatile, Definition> {
+    use Metatile::{F, H, P, T};
+    use PatchRule::{Edge, Vertices};
+
+    let rules = [
+        Edge(0, 0, P, 2),
+        Edge(1, 0, H, 2),
+        Edge(2, 0, P, 2),
+        Edge(3, 0, H, 2),
+        Edge(4, 4, P, 2),
+        Edge(0, 4, F, 3),
+        Edge(2, 4, F, 3),
+        Vertices(4, 1, 3, 2, F, 0),
+        Edge(8, 3, H, 0),
+        Edge(9, 2, P, 0),
+        Edge(10, 2, H, 0),
+        Edge(11, 4, P, 2),
+        Edge(12, 0, H, 2),
+        Edge(13, 0, F, 3),
+        Edge(14, 2, F, 1),
+        Edge(15, 3, H, 4),
+        Edge(8, 2, F, 1),
+        Edge(17, 3, H, 0),
+        Edge(18, 2, P, 0),
+        Edge(19, 2, H, 2),
+        Edge(20, 4, F, 3),
+        Edge(20, 0, P, 2),
+        Edge(22, 0, H, 2),
+        Edge(23, 4, F, 3),
+        Edge(23, 0, F, 3),
+        Edge(16, 0, P, 2),
+        Vertices(9, 4, 0, 2, T, 2),
+        Edge(4, 0, F, 3),
+    ];
+
+    let mut patch = vec![(Affine::identity(), H)];
+
+    let vertex = |patch: &[(Affine, Metatile)], child: usize, index: usize| {
+        let (transform, metatile) = &patch[child];
+        let outline = &metatiles[metatile].outline;
+
+        transform.apply(&outline[index % outline.len()])
+    };
+
+    for rule in rules.iter() {
+        let (p, q, metatile, index) = match *rule {
+            Edge(child, index, metatile, other_index) => (
+                vertex(&patch, child, index + 1),
+                vertex(&patch, child, index),
+                metatile,
+                other_index,
+            ),
+            Vertices(child, index, other_child, other_index, metatile, new_index) => (
+                vertex(&patch, other_child, other_index),
+                vertex(&patch, child, index),
+                metatile,
+                new_index,
+            ),
+        };
+
+        let outline = &metatiles[&metatile].outline;
+        let transform = match_segments(outline[index], outline[(index + 1) % outline.len()], p, q);
+
+        patch.push((transform, metatile));
+    }
+
+    let point = |child: usize, index: usize| vertex(&patch, child, index);
+
+    // The outlines of the supertiles are spanned by vertices of the patch's metatiles, most of
+    // them are shared by several supertiles.
+    let (first, second) = (point(8, 2), point(21, 2));
+    let rotated = rotation_about(first, -2.0 * PI / 3.0).apply(&second);
+    let (p_corner, f_corner) = (point(7, 2), point(25, 2));
+
+    let lower_left = intersect_lines(first, rotated, point(6, 2), p_corner);
+    let mut edge = point(6, 2) - lower_left;
+
+    let mut h_outline = vec![lower_left, first];
+    edge = Affine::rotation(-PI / 3.0).apply(&edge);
+    h_outline.push(h_outline[1] + edge);
+    h_outline.push(point(14, 2));
+    edge = Affine::rotation(-PI / 3.0).apply(&edge);
+    h_outline.push(h_outline[3] - edge);
+    h_outline.push(point(6, 2));
+
+    let p_outline = vec![p_corner, p_corner + (first - lower_left), first, lower_left];
+
+    let f_outline = vec![
+        second,
+        point(24, 2),
+        point(25, 0),
+        f_corner,
+        f_corner + (lower_left - first),
+    ];
+
+    let a = h_outline[2];
+    let b = h_outline[1] + (h_outline[4] - h_outline[5]);
+    let c = rotation_about(b, -PI / 3.0).apply(&a);
+    let t_outline = vec![b, c, a];
+
+    [
+        (H, h_outline, vec![0, 9, 16, 27, 26, 6, 1, 8, 10, 15]),
+        (T, t_outline, vec![11]),
+        (P, p_outline, vec![7, 2, 3, 4, 28]),
+        (F, f_outline, vec![21, 20, 22, 23, 24, 25]),
+    ]
+    .iter()
+    .map(|(metatile, outline, children)| {
+        // Every supertile is moved so that the centroid of its outline is at the origin.
+        let centroid = geometry::centroid(outline);
+        let recentre = Affine::translation(-centroid.x, -centroid.y);
+
+        let definition = Definition {
+            outline: outline.iter().map(|point| recentre.apply(point)).collect(),
+            children: children
+                .iter()
+                .map(|&child| (patch[child].0.then(&recentre), patch[child].1))
+                .collect(),
+        };
+
+        (*metatile, definition)
+    })
+    .collect()
+}
+
+/// Returns the key points of the Spectre, which are used to place neighbouring clusters.
+fn spectre_key_points() -> Vec<Point2> {
+    let spectre = spectre_outline();
+
+    vec![spectre[3], spectre[5], spectre[7], spectre[11]]
+}
+
+/// Returns the clusters of the Spectre tiling, made of spectres.
+fn spectre_clusters() -> HashMap<Metatile, Definition> {
+    use Metatile::*;
+
+    let spectre = spectre_outline();
+    let mut clusters: HashMap<Metatile, Definition> =
+        [Delta, Theta, Lambda, Xi, Pi, Sigma, Phi, Psi]
+            .iter()
+            .map(|&metatile| {
+                let definition = Definition {
+                    outline: spectre_key_points(),
+                    children: vec![(Affine::identity(), metatile)],
+                };
+
+                (metatile, definition)
+            })
+            .collect();
+
+    // The mystic, a pair of spectres, one of which is rotated by 30 degrees.
+    clusters.insert(
+        Gamma,
+        Definition {
+            outline: spectre_key_points(),
+            children: vec![
+                (Affine::identity(), Gamma),
+                (
+                    Affine::rotation(PI / 6.0)
+                        .then(&Affine::translation(spectre[8].x, spectre[8].y)),
+                    Gamma,
+                ),
+            ],
+        },
+    );
+
+    clusters
+}
+
+/// Returns the clusters of the Spectre tiling, made of the clusters of the level below.
+fn spectre_supertiles(clusters: &HashMap<Metatile, Definition>) -> HashMap<Metatile, Definition> {
+    use Metatile::*;
+
+    let key_points = &clusters[&Delta].outline;
+
+    // Every cluster is made of the same eight placements, each of which is rotated by the angle
+    // in degrees relative to the one before it and moved so that its key point at the second
+    // index meets the previous one's key point at the first index.
+    let rules = [
+        (60.0, 3, 1),
+        (0.0, 2, 0),
+        (60.0, 3, 1),
+        (60.0, 3, 1),
+        (0.0, 2, 0),
+        (60.0, 3, 1),
+        (-120.0, 3, 3),
+    ];
+
+    let mut transforms = vec![Affine::identity()];
+    let mut angle: f32 = 0.0;
+
+    for &(rotation, from, to) in rules.iter() {
+        angle += rotation;
+
+        let rotation = Affine::rotation(angle.to_radians());
+        let previous = transforms[transforms.len() - 1].apply(&key_points[from]);
+        let offset = previous - rotation.apply(&key_points[to]);
+
+        transforms.push(rotation.then(&Affine::translation(offset.x, offset.y)));
+    }
+
+    // The clusters of every other level are mirror images.
+    let transforms: Vec<Affine> = transforms
+        .iter()
+        .map(|transform| transform.then(&Affine::scale(-1.0, 1.0)))
+        .collect();
+
+    let outline = vec![
+        transforms[6].apply(&key_points[2]),
+        transforms[5].apply(&key_points[1]),
+        transforms[3].apply(&key_points[2]),
+        transforms[0].apply(&key_points[1]),
+    ];
+
+    let rules = [
+        (Gamma, [Pi, Delta, Gamma, Theta, Sigma, Xi, Phi, Gamma]),
+        (Delta, [Xi, Delta, Xi, Phi, Sigma, Pi, Phi, Gamma]),
+        (Theta, [Psi, Delta, Pi, Phi, Sigma, Pi, Phi, Gamma]),
+        (Lambda, [Psi, Delta, Xi, Phi, Sigma, Pi, Phi, Gamma]),
+        (Xi, [Psi, Delta, Pi, Phi, Sigma, Psi, Phi, Gamma]),
+        (Pi, [Psi, Delta, Xi, Phi, Sigma, Psi, Phi, Gamma]),
+        (Sigma, [Xi, Delta, Xi, Phi, Sigma, Pi, Lambda, Gamma]),
+        (Phi, [Psi, Delta, Psi, Phi, Sigma, Pi, Phi, Gamma]),
+        (Psi, [Psi, Delta, Psi, Phi, Sigma, Psi, Phi, Gamma]),
+    ];
+
+    rules
+        .iter()
+        .map(|&(metatile, children)| {
+            let definition = Definition {
+                outline: outline.clone(),
+                children: children
+                    .iter()
+                    .zip(&transforms)
+                    .enumerate()
+                    // The Γ cluster has no child at the third placement, the second spectre of
+                    // the mystic at its last placement takes up the space.
+                    .filter(|(index, _)| metatile != Gamma || *index != 2)
+                    .map(|(_, (child, transform))| (*transform, *child))
+                    .collect(),
+            };
+
+            (metatile, definition)
+        })
+        .collect()
+}
+
+/// Returns the transform which maps the points (0, 0) and (1, 0) onto p and q, a rotation and
+/// a uniform scale followed by a translation.
+fn segment_transform(p: Point2, q: Point2) -> Affine {
+    Affine::new(q.x - p.x, p.y - q.y, p.x, q.y - p.y, q.x - p.x, p.y)
+}
+
+/// Returns the transform which maps the segment from p1 to q1 onto the segment from p2 to q2.
+fn match_segments(p1: Point2, q1: Point2, p2: Point2, q2: Point2) -> Affine {
+    segment_transform(p1, q1)
+        .inverse()
+        .expect("The segment has a length")
+        .then(&segment_transform(p2, q2))
+}
+
+/// Returns the counter-clockwise rotation around the point, by an angle in radians.
+fn rotation_about(point: Point2, angle: f32) -> Affine {
+    Affine::translation(-point.x, -point.y)
+        .then(&Affine::rotation(angle))
+        .then(&Affine::translation(point.x, point.y))
+}
+
+/// Returns the intersection of the line through p1 and q1 with the line through p2 and q2.
+fn intersect_lines(p1: Point2, q1: Point2, p2: Point2, q2: Point2) -> Point2 {
+    let denominator = (q2.y - p2.y) * (q1.x - p1.x) - (q2.x - p2.x) * (q1.y - p1.y);
+    let t = ((q2.x - p2.x) * (p1.y - p2.y) - (q2.y - p2.y) * (p1.x - p2.x)) / denominator;
+
+    p1 + (q1 - p1) * t
+}
@@ -0,0 +1,311 @@
+//! Implementation of the Penrose P2 (kites and darts) and P3 (rhombs) tilings.
+//!
+//! Both tilings are constructed by the substitution of Robinson triangles, the halves of the
+//! Penrose tiles. An acute triangle (36-72-72 degrees) is half of a kite or of a thin rhomb,
+//! an obtuse triangle (108-36-36 degrees) is half of a dart or of a thick rhomb. Every division
+//! shrinks the triangles by the golden ratio. The halves can be used as tiles on their own or be
+//! joined back into whole tiles with [`assemble`].
+use super::{
+    polygon::{Affine, Polygon},
+    Divisible, Labelled, Polygonal,
+};
+use crate::geometry::polygon as geometry;
+use nannou::geom::{Point2, Vector2};
+use std::{collections::HashMap, f32::consts::PI};
+
+const GOLDEN_RATIO: f32 = 1.618_034;
+
+/// Enumeration of the two Penrose tilings built from Robinson triangles.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PenroseVariant {
+    /// P2, made of kites and darts.
+    KitesAndDarts,
+    /// P3, made of thin and thick rhombs.
+    Rhombs,
+}
+
+/// Enumeration of the two Robinson triangles.
+///
+/// Both triangles are isosceles, their apex is the vertex between the two equal legs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RobinsonTriangle {
+    /// The apex has an angle of 36 degrees.
+    Acute,
+    /// The apex has an angle of 108 degrees.
+    Obtuse,
+}
+
+/// Enumeration of the whole tiles of the Penrose tilings.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PenroseTileType {
+    Kite,
+    Dart,
+    ThinRhomb,
+    ThickRhomb,
+}
+
+/// A Robinson triangle of a Penrose tiling.
+///
+/// The triangle's apex is a, its legs are a-b and a-c. The order of the vertices encodes the
+/// triangle's reflection, which decides how it is divided, so that the halves of every tile
+/// are mirror images of each other.
+#[derive(Debug, Clone)]
+pub struct PenroseTile {
+    pub variant: PenroseVariant,
+    pub triangle: RobinsonTriangle,
+    pub a: Point2,
+    pub b: Point2,
+    pub c: Point2,
+}
+
+impl PenroseTile {
+    /// Creates the ten acute triangles of a sun, a decagon around the center whose corners
+    /// lie radius away from it. In P2 the sun is made of five kites.
+    pub fn sun(variant: PenroseVariant, center: Point2, radius: f32) -> Vec<Self> {
+        (0..10)
+            .map(|i| {
+                let (b, c) = wheel_spokes(center, i, radius, radius);
+
+                PenroseTile {
+                    variant,
+                    triangle: RobinsonTriangle::Acute,
+                    a: center,
+                    b,
+                    c,
+                }
+            })
+            .collect()
+    }
+
+    /// Creates the ten obtuse triangles of a star, whose points lie radius away from the center.
+    /// In P2 the star is made of five darts, in P3 of five thick rhombs.
+    pub fn star(variant: PenroseVariant, center: Point2, radius: f32) -> Vec<Self> {
+        (0..10)
+            .map(|i| {
+                let (a, b) = wheel_spokes(center, i, radius / GOLDEN_RATIO, radius);
+
+                PenroseTile {
+                    variant,
+                    triangle: RobinsonTriangle::Obtuse,
+                    a,
+                    b,
+                    c: center,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the type of the whole tile which the triangle is half of.
+    pub fn tile_type(&self) -> PenroseTileType {
+        match (self.variant, self.triangle) {
+            (PenroseVariant::KitesAndDarts, RobinsonTriangle::Acute) => PenroseTileType::Kite,
+            (PenroseVariant::KitesAndDarts, RobinsonTriangle::Obtuse) => PenroseTileType::Dart,
+            (PenroseVariant::Rhombs, RobinsonTriangle::Acute) => PenroseTileType::ThinRhomb,
+            (PenroseVariant::Rhombs, RobinsonTriangle::Obtuse) => PenroseTileType::ThickRhomb,
+        }
+    }
+
+    /// Returns the edge along which the triangle meets the other half of its tile, the axis of
+    /// symmetry of kites and darts and the short, respectively long, diagonal of rhombs.
+    pub fn shared_edge(&self) -> (Point2, Point2) {
+        match self.variant {
+            PenroseVariant::KitesAndDarts => (self.a, self.c),
+            PenroseVariant::Rhombs => (self.b, self.c),
+        }
+    }
+
+    /// Checks whether the triangle's vertices are in clockwise order, i.e. whether it is
+    /// the mirrored half of its tile.
+    pub fn is_reflected(&self) -> bool {
+        geometry::signed_area(&[self.a, self.b, self.c]) < 0.0
+    }
+
+    fn child(&self, triangle: RobinsonTriangle, a: Point2, b: Point2, c: Point2) -> Self {
+        PenroseTile {
+            variant: self.variant,
+            triangle,
+            a,
+            b,
+            c,
+        }
+    }
+}
+
+impl Divisible for PenroseTile {
+    fn divide(&self) -> Vec<Self> {
+        use RobinsonTriangle::{Acute, Obtuse};
+
+        let (a, b, c) = (self.a, self.b, self.c);
+
+        match (self.variant, self.triangle) {
+            (PenroseVariant::Rhombs, Acute) => {
+                let p = a + (b - a) / GOLDEN_RATIO;
+
+                vec![self.child(Acute, c, p, b), self.child(Obtuse, p, c, a)]
+            }
+            (PenroseVariant::Rhombs, Obtuse) => {
+                let q = b + (a - b) / GOLDEN_RATIO;
+                let r = b + (c - b) / GOLDEN_RATIO;
+
+                vec![
+                    self.child(Obtuse, r, c, a),
+                    self.child(Obtuse, q, r, b),
+                    self.child(Acute, r, q, a),
+                ]
+            }
+            (PenroseVariant::KitesAndDarts, Acute) => {
+                let q = a + (b - a) / (GOLDEN_RATIO * GOLDEN_RATIO);
+                let p = a + (c - a) / GOLDEN_RATIO;
+
+                vec![
+                    self.child(Acute, b, c, p),
+                    self.child(Acute, b, q, p),
+                    self.child(Obtuse, q, p, a),
+                ]
+            }
+            (PenroseVariant::KitesAndDarts, Obtuse) => {
+                let r = b + (c - b) / (GOLDEN_RATIO * GOLDEN_RATIO);
+
+                vec![self.child(Acute, c, r, a), self.child(Obtuse, r, a, b)]
+            }
+        }
+    }
+}
+
+impl Polygonal for PenroseTile {
+    fn vertices(&self) -> Vec<Point2> {
+        vec![self.a, self.b, self.c]
+    }
+}
+
+impl Labelled for PenroseTile {
+    fn label(&self) -> &str {
+        match self.triangle {
+            RobinsonTriangle::Acute => "acute",
+            RobinsonTriangle::Obtuse => "obtuse",
+        }
+    }
+}
+
+/// A whole Penrose tile, or a lone half of one on the border of a patch.
+#[derive(Debug, Clone)]
+pub struct AssembledTile {
+    pub tile_type: PenroseTileType,
+    pub polygon: Polygon,
+    /// False if the other half of the tile is missing from the patch.
+    pub is_complete: bool,
+}
+
+/// Joins the triangles which are halves of the same tile into whole tiles, in counter-clockwise
+/// order. Triangles without their other half are returned on their own.
+pub fn assemble(triangles: &[PenroseTile]) -> Vec<AssembledTile> {
+    // Halves are matched by the midpoints of their shared edges, which are bucketed into cells
+    // much smaller than the triangles, so that rounding errors don't prevent a match.
+    let cell_size = triangles
+        .iter()
+        .map(|t| (t.b - t.a).magnitude())
+        .fold(f32::MAX, f32::min)
+        / 8.0;
+
+    let calculate_cell = |point: &Point2| {
+        (
+            (point.x / cell_size).floor() as i64,
+            (point.y / cell_size).floor() as i64,
+        )
+    };
+
+    let midpoint = |t: &PenroseTile| {
+        let (p, q) = t.shared_edge();
+        p + (q - p) / 2.0
+    };
+
+    let mut cells: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+
+    for (index, triangle) in triangles.iter().enumerate() {
+        cells
+            .entry(calculate_cell(&midpoint(triangle)))
+            .or_default()
+            .push(index);
+    }
+
+    let mut is_assembled = vec![false; triangles.len()];
+    let mut tiles = vec![];
+
+    for (index, triangle) in triangles.iter().enumerate() {
+        if is_assembled[index] {
+            continue;
+        }
+
+        is_assembled[index] = true;
+
+        let center = midpoint(triangle);
+        let (x, y) = calculate_cell(&center);
+
+        let partner = (x - 1..=x + 1)
+            .flat_map(|x| (y - 1..=y + 1).map(move |y| (x, y)))
+            .filter_map(|cell| cells.get(&cell))
+            .flatten()
+            .copied()
+            .find(|&other| {
+                !is_assembled[other]
+                    && triangles[other].triangle == triangle.triangle
+                    && (midpoint(&triangles[other]) - center).magnitude() < cell_size / 4.0
+            });
+
+        let vertices = match partner {
+            Some(other) => {
+                is_assembled[other] = true;
+
+                let (p, q) = triangle.shared_edge();
+
+                vec![
+                    p,
+                    opposite_vertex(triangle),
+                    q,
+                    opposite_vertex(&triangles[other]),
+                ]
+            }
+            None => triangle.vertices(),
+        };
+
+        let vertices = match geometry::signed_area(&vertices) < 0.0 {
+            true => vertices.into_iter().rev().collect(),
+            false => vertices,
+        };
+
+        tiles.push(AssembledTile {
+            tile_type: triangle.tile_type(),
+            polygon: Polygon::new(vertices, Affine::identity()),
+            is_complete: partner.is_some(),
+        });
+    }
+
+    tiles
+}
+
+/// Returns the vertex of the triangle which isn't on its shared edge.
+fn opposite_vertex(triangle: &PenroseTile) -> Point2 {
+    match triangle.variant {
+        PenroseVariant::KitesAndDarts => triangle.b,
+        PenroseVariant::Rhombs => triangle.a,
+    }
+}
+
+/// Returns the two outer vertices of the i-th of ten triangles around a center, at the given
+/// distances. Every second triangle is mirrored, so that neighbours meet with matching vertices.
+fn wheel_spokes(center: Point2, i: u32, first: f32, second: f32) -> (Point2, Point2) {
+    let (start, end) = (
+        (2.0 * i as f32 - 1.0) * PI / 10.0,
+        (2.0 * i as f32 + 1.0) * PI / 10.0,
+    );
+
+    let (first_angle, second_angle) = match i % 2 {
+        0 => (end, start),
+        _ => (start, end),
+    };
+
+    (
+        center + Vector2::from_angle(first_angle) * first,
+        center + Vector2::from_angle(second_angle) * second,
+    )
+}
@@ -0,0 +1,160 @@
+//! Implementation of the pinwheel tiling.
+//!
+//! This tiling uses a right triangle with legs of length 1 and 2 as its proto-tile. Each triangle
+//! is divided into five smaller copies of itself, shrunk by the square root of five. Because the
+//! children are rotated by an angle which is an irrational multiple of π, the tiles of a pinwheel
+//! tiling point in infinitely many directions.
+//!
+//! Every tile is a [`Polygon`] whose transform places the proto-tile in the plane. The tile's
+//! orientation and chirality are both read off that transform, see [`PinwheelTile::orientation`]
+//! and [`PinwheelTile::chirality`].
+use super::{
+    polygon::{rect_vertices, Affine, Polygon},
+    Divisible, Labelled, Polygonal,
+};
+use nannou::geom::{pt2, Point2, Rect};
+
+/// Enumeration of the two reflections of the pinwheel triangle.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Chirality {
+    /// The right angle, the end of the long leg and the end of the short leg are in
+    /// counter-clockwise order.
+    LeftHanded,
+    /// The mirror image of a left handed triangle.
+    RightHanded,
+}
+
+/// A triangle of the pinwheel tiling.
+#[derive(Debug, Clone)]
+pub struct PinwheelTile {
+    polygon: Polygon,
+}
+
+impl PinwheelTile {
+    /// Constructs a tile which is the proto-tile placed by the transform.
+    pub fn new(transform: Affine) -> Self {
+        Self {
+            polygon: Polygon::new(prototile(), transform),
+        }
+    }
+
+    /// Creates the two triangles which cover a rect, split along its diagonal from the top left
+    /// to the bottom right corner.
+    ///
+    /// The triangles are only right triangles with legs of length 1 and 2 if the rect is twice
+    /// as wide as it's high, otherwise the whole tiling is stretched to fit the rect.
+    pub fn from_rect(rect: &Rect, chirality: Chirality) -> Vec<Self> {
+        let corners = rect_vertices(rect);
+        let (bottom_left, bottom_right, top_right, top_left) =
+            (corners[0], corners[1], corners[2], corners[3]);
+
+        let tiles = vec![
+            PinwheelTile::new(map_onto(bottom_left, bottom_right, top_left)),
+            PinwheelTile::new(map_onto(top_right, top_left, bottom_right)),
+        ];
+
+        match chirality {
+            Chirality::LeftHanded => tiles,
+            // The mirror image of the rect's tiles, reflected across its vertical center line.
+            Chirality::RightHanded => {
+                let reflection = Affine::translation(-rect.x(), 0.0)
+                    .then(&Affine::scale(-1.0, 1.0))
+                    .then(&Affine::translation(rect.x(), 0.0));
+
+                tiles
+                    .iter()
+                    .map(|tile| PinwheelTile::new(tile.transform().then(&reflection)))
+                    .collect()
+            }
+        }
+    }
+
+    pub fn polygon(&self) -> &Polygon {
+        &self.polygon
+    }
+
+    pub fn transform(&self) -> &Affine {
+        self.polygon.transform()
+    }
+
+    /// Returns the direction of the triangle's long leg, pointing from the right angle to its
+    /// other end, as an angle in radians in the range [-π..π].
+    pub fn orientation(&self) -> f32 {
+        let transform = self.transform();
+
+        transform.d.atan2(transform.a)
+    }
+
+    pub fn chirality(&self) -> Chirality {
+        match self.transform().is_reflection() {
+            true => Chirality::RightHanded,
+            false => Chirality::LeftHanded,
+        }
+    }
+
+    /// Returns the vertex with the right angle.
+    pub fn right_angle(&self) -> Point2 {
+        self.transform().apply(&pt2(0.0, 0.0))
+    }
+}
+
+impl Divisible for PinwheelTile {
+    fn divide(&self) -> Vec<Self> {
+        // The proto-tile is split along the altitude onto its hypotenuse, into a small triangle
+        // and one twice as large. The large one is cut into two triangles and a rectangle, whose
+        // diagonal splits it into two mirrored triangles.
+        let (origin, long, short) = (pt2(0.0, 0.0), pt2(2.0, 0.0), pt2(0.0, 1.0));
+        let altitude = pt2(0.4, 0.8);
+        let (long_midpoint, hypotenuse_point, altitude_midpoint) =
+            (pt2(1.0, 0.0), pt2(1.2, 0.4), pt2(0.2, 0.4));
+
+        [
+            map_onto(altitude, origin, short),
+            map_onto(hypotenuse_point, long, long_midpoint),
+            map_onto(altitude_midpoint, long_midpoint, origin),
+            map_onto(hypotenuse_point, altitude, long_midpoint),
+            map_onto(altitude_midpoint, long_midpoint, altitude),
+        ]
+        .iter()
+        .map(|relative| PinwheelTile {
+            polygon: self.polygon.place(prototile(), relative),
+        })
+        .collect()
+    }
+}
+
+impl Polygonal for PinwheelTile {
+    fn vertices(&self) -> Vec<Point2> {
+        self.polygon.vertices()
+    }
+}
+
+impl Labelled for PinwheelTile {
+    fn label(&self) -> &str {
+        match self.chirality() {
+            Chirality::LeftHanded => "left-handed",
+            Chirality::RightHanded => "right-handed",
+        }
+    }
+}
+
+/// Returns the proto-tile, with the right angle at the origin, the long leg along the x axis and
+/// the short leg along the y axis.
+fn prototile() -> Vec<Point2> {
+    vec![pt2(0.0, 0.0), pt2(2.0, 0.0), pt2(0.0, 1.0)]
+}
+
+/// Returns the transform which maps the proto-tile onto the triangle with the right angle at
+/// right_angle and the ends of its long and short legs at long and short respectively.
+fn map_onto(right_angle: Point2, long: Point2, short: Point2) -> Affine {
+    let (x_axis, y_axis) = ((long - right_angle) / 2.0, short - right_angle);
+
+    Affine::new(
+        x_axis.x,
+        y_axis.x,
+        right_angle.x,
+        x_axis.y,
+        y_axis.y,
+        right_angle.y,
+    )
+}
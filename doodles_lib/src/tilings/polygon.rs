@@ -0,0 +1,164 @@
+//! Polygon based tile geometry, for tilings whose tiles aren't axis-aligned rects.
+//!
+//! A [`Polygon`] is made of the vertices of a prototile and an [`Affine`] transform which places
+//! them in the plane. Substitution tilings divide a tile by placing each child's prototile with
+//! a transform relative to its parent, so the children's transforms are simply the parent's
+//! transform composed with the relative ones.
+use super::Polygonal;
+use nannou::geom::{Point2, Rect};
+
+/// An affine transform of the plane, which maps a point (x, y) to
+/// (a * x + b * y + c, d * x + e * y + f).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Affine {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Affine {
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0)
+    }
+
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub fn translation(x: f32, y: f32) -> Self {
+        Self::new(1.0, 0.0, x, 0.0, 1.0, y)
+    }
+
+    /// A counter-clockwise rotation around the origin, by an angle in radians.
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+
+        Self::new(cos, -sin, 0.0, sin, cos, 0.0)
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self::new(x, 0.0, 0.0, 0.0, y, 0.0)
+    }
+
+    /// A reflection across the x axis, which flips the sign of y.
+    pub fn reflection() -> Self {
+        Self::scale(1.0, -1.0)
+    }
+
+    /// Returns the transform which applies this transform first and the other one afterwards.
+    pub fn then(&self, other: &Affine) -> Self {
+        Self::new(
+            other.a * self.a + other.b * self.d,
+            other.a * self.b + other.b * self.e,
+            other.a * self.c + other.b * self.f + other.c,
+            other.d * self.a + other.e * self.d,
+            other.d * self.b + other.e * self.e,
+            other.d * self.c + other.e * self.f + other.f,
+        )
+    }
+
+    pub fn apply(&self, point: &Point2) -> Point2 {
+        Point2::new(
+            self.a * point.x + self.b * point.y + self.c,
+            self.d * point.x + self.e * point.y + self.f,
+        )
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.a * self.e - self.b * self.d
+    }
+
+    /// Checks whether the transform mirrors the plane, i.e. changes the handedness of shapes.
+    pub fn is_reflection(&self) -> bool {
+        self.determinant() < 0.0
+    }
+
+    /// Returns the inverse transform, or None if the transform collapses the plane.
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+
+        if determinant == 0.0 {
+            return None;
+        }
+
+        let (a, b, d, e) = (
+            self.e / determinant,
+            -self.b / determinant,
+            -self.d / determinant,
+            self.a / determinant,
+        );
+
+        Some(Self::new(
+            a,
+            b,
+            -(a * self.c + b * self.f),
+            d,
+            e,
+            -(d * self.c + e * self.f),
+        ))
+    }
+}
+
+/// The vertices of a prototile, placed in the plane by a transform.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    prototile: Vec<Point2>,
+    transform: Affine,
+}
+
+impl Polygon {
+    pub fn new(prototile: Vec<Point2>, transform: Affine) -> Self {
+        Self {
+            prototile,
+            transform,
+        }
+    }
+
+    /// Constructs a polygon with the corners of a rect, in counter-clockwise order starting
+    /// from the bottom left one.
+    pub fn from_rect(rect: &Rect) -> Self {
+        Self::new(rect_vertices(rect), Affine::identity())
+    }
+
+    /// Returns the untransformed vertices of the prototile.
+    pub fn prototile(&self) -> &[Point2] {
+        &self.prototile
+    }
+
+    pub fn transform(&self) -> &Affine {
+        &self.transform
+    }
+
+    /// Returns a copy of the polygon, moved by the transform after its own transform.
+    pub fn transformed(&self, transform: &Affine) -> Self {
+        Self::new(self.prototile.clone(), self.transform.then(transform))
+    }
+
+    /// Places another prototile relative to this polygon, i.e. the prototile is first moved by
+    /// the relative transform and then by the polygon's own transform.
+    pub fn place(&self, prototile: Vec<Point2>, relative: &Affine) -> Self {
+        Self::new(prototile, relative.then(&self.transform))
+    }
+}
+
+impl Polygonal for Polygon {
+    fn vertices(&self) -> Vec<Point2> {
+        self.prototile
+            .iter()
+            .map(|vertex| self.transform.apply(vertex))
+            .collect()
+    }
+}
+
+/// Returns the corners of a rect, in counter-clockwise order starting from the bottom left one.
+pub(crate) fn rect_vertices(rect: &Rect) -> Vec<Point2> {
+    vec![
+        rect.bottom_left(),
+        rect.bottom_right(),
+        rect.top_right(),
+        rect.top_left(),
+    ]
+}
@@ -0,0 +1,230 @@
+//! Implementation of substitution tilings whose rules are data instead of code.
+//!
+//! The rules of a tiling are a list of prototiles, each with a unique label, the vertices of its
+//! polygon and its children. A child is the label of a prototile and the [`Affine`] transform
+//! which places it relative to its parent, in the coordinates of the parent's vertices. Rules are
+//! written in TOML, where a transform is the list `[a, b, c, d, e, f]` of its coefficients:
+//!
+//! ```toml
+//! [[prototiles]]
+//! label = "square"
+//! vertices = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+//! children = [
+//!     { label = "square", transform = [0.5, 0.0, 0.0, 0.0, 0.5, 0.0] },
+//!     { label = "square", transform = [0.5, 0.0, 0.5, 0.0, 0.5, 0.0] },
+//!     { label = "square", transform = [0.5, 0.0, 0.0, 0.0, 0.5, 0.5] },
+//!     { label = "square", transform = [0.5, 0.0, 0.5, 0.0, 0.5, 0.5] },
+//! ]
+//! ```
+//!
+//! The domino and wanderer tilings are available as rules as well, see
+//! [`SubstitutionRules::domino`] and [`SubstitutionRules::wanderer`].
+use super::{
+    polygon::{Affine, Polygon},
+    Divisible, Labelled, Polygonal,
+};
+use crate::geometry::polygon as geometry;
+use nannou::geom::{pt2, Point2, Rect};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+    rc::Rc,
+};
+
+const DOMINO_RULES: &str = include_str!("rules/domino.toml");
+const WANDERER_RULES: &str = include_str!("rules/wanderer.toml");
+
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    prototiles: Vec<PrototileEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrototileEntry {
+    label: String,
+    vertices: Vec<[f32; 2]>,
+    #[serde(default)]
+    children: Vec<ChildEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChildEntry {
+    label: String,
+    transform: [f32; 6],
+}
+
+#[derive(Debug)]
+struct Prototile {
+    label: String,
+    vertices: Vec<Point2>,
+    /// The placements of the children and the indices of their prototiles.
+    children: Vec<(Affine, usize)>,
+}
+
+/// The validated rules of a substitution tiling.
+#[derive(Debug)]
+pub struct SubstitutionRules {
+    prototiles: Vec<Prototile>,
+}
+
+impl SubstitutionRules {
+    /// Parses rules written in TOML, see the module's documentation for their format.
+    ///
+    /// Fails with [`ErrorKind::InvalidData`] if the rules can't be parsed, if a prototile has
+    /// fewer than three vertices, if two prototiles have the same label, or if a child isn't
+    /// one of the prototiles.
+    pub fn from_toml(source: &str) -> io::Result<Self> {
+        let file: RulesFile =
+            toml::from_str(source).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+
+        let mut indices = HashMap::new();
+
+        for (index, prototile) in file.prototiles.iter().enumerate() {
+            if prototile.vertices.len() < 3 {
+                return Err(invalid_data(format!(
+                    "The prototile {} has fewer than three vertices.",
+                    prototile.label
+                )));
+            }
+
+            if indices.insert(prototile.label.as_str(), index).is_some() {
+                return Err(invalid_data(format!(
+                    "The label {} is used by more than one prototile.",
+                    prototile.label
+                )));
+            }
+        }
+
+        let prototiles = file
+            .prototiles
+            .iter()
+            .map(|prototile| {
+                let children = prototile
+                    .children
+                    .iter()
+                    .map(|child| match indices.get(child.label.as_str()) {
+                        Some(&index) => {
+                            let [a, b, c, d, e, f] = child.transform;
+                            Ok((Affine::new(a, b, c, d, e, f), index))
+                        }
+                        None => Err(invalid_data(format!(
+                            "The child {} of the prototile {} isn't a prototile.",
+                            child.label, prototile.label
+                        ))),
+                    })
+                    .collect::<io::Result<Vec<_>>>()?;
+
+                Ok(Prototile {
+                    label: prototile.label.clone(),
+                    vertices: prototile.vertices.iter().map(|&[x, y]| pt2(x, y)).collect(),
+                    children,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Self { prototiles })
+    }
+
+    /// Reads and parses rules from a TOML file, see [`SubstitutionRules::from_toml`].
+    pub fn load<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// The rules of the domino tiling, with the prototiles `horizontal` and `vertical`.
+    pub fn domino() -> Self {
+        Self::from_toml(DOMINO_RULES).expect("The domino rules are valid.")
+    }
+
+    /// The rules of the wanderer tiling, with the prototiles `left-handed-bottom`,
+    /// `right-handed-top` and so on, for both handednesses and all four orientations.
+    pub fn wanderer() -> Self {
+        Self::from_toml(WANDERER_RULES).expect("The wanderer rules are valid.")
+    }
+
+    /// Returns the labels of all prototiles, in the order of the rules.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.prototiles
+            .iter()
+            .map(|prototile| prototile.label.as_str())
+    }
+
+    fn index(&self, label: &str) -> Option<usize> {
+        self.prototiles
+            .iter()
+            .position(|prototile| prototile.label == label)
+    }
+}
+
+/// A tile of a substitution tiling, a prototile placed in the plane.
+#[derive(Debug, Clone)]
+pub struct SubstitutionTile {
+    rules: Rc<SubstitutionRules>,
+    prototile: usize,
+    polygon: Polygon,
+}
+
+impl SubstitutionTile {
+    /// Constructs a tile of the prototile with the label, placed by the transform, or None if
+    /// the rules have no such prototile.
+    pub fn new(rules: &Rc<SubstitutionRules>, label: &str, transform: Affine) -> Option<Self> {
+        rules.index(label).map(|prototile| Self {
+            polygon: Polygon::new(rules.prototiles[prototile].vertices.clone(), transform),
+            rules: rules.clone(),
+            prototile,
+        })
+    }
+
+    /// Constructs a tile of the prototile with the label, whose bounding box is stretched to
+    /// the rect, or None if the rules have no such prototile.
+    pub fn from_rect(rules: &Rc<SubstitutionRules>, label: &str, rect: &Rect) -> Option<Self> {
+        let bounds = geometry::bounds(&rules.prototiles[rules.index(label)?].vertices);
+
+        let transform = Affine::translation(-bounds.left(), -bounds.bottom())
+            .then(&Affine::scale(rect.w() / bounds.w(), rect.h() / bounds.h()))
+            .then(&Affine::translation(rect.left(), rect.bottom()));
+
+        Self::new(rules, label, transform)
+    }
+
+    pub fn polygon(&self) -> &Polygon {
+        &self.polygon
+    }
+}
+
+impl Divisible for SubstitutionTile {
+    fn divide(&self) -> Vec<Self> {
+        self.rules.prototiles[self.prototile]
+            .children
+            .iter()
+            .map(|(relative, prototile)| SubstitutionTile {
+                rules: self.rules.clone(),
+                prototile: *prototile,
+                polygon: self
+                    .polygon
+                    .place(self.rules.prototiles[*prototile].vertices.clone(), relative),
+            })
+            .collect()
+    }
+}
+
+impl Polygonal for SubstitutionTile {
+    fn vertices(&self) -> Vec<Point2> {
+        self.polygon.vertices()
+    }
+}
+
+impl Labelled for SubstitutionTile {
+    fn label(&self) -> &str {
+        &self.rules.prototiles[self.prototile].label
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
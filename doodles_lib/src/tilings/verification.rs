@@ -0,0 +1,222 @@
+//! Implementation of checks which verify that tiles are divided into valid tilings.
+//!
+//! The children of a tile must cover their parent without any gaps or overlaps. Three invariants
+//! follow from that, which are checked for every polygonal, divisible tile:
+//!
+//! - the areas of the children sum up to the area of the parent,
+//! - the interiors of the children are pairwise disjoint,
+//! - the union of the children covers the parent and nothing else.
+//!
+//! Areas and crossing edges are checked exactly, up to a tolerance relative to the size of the
+//! parent. Coverage is checked on a grid of sample points, so defects smaller than the spacing of
+//! the grid might be missed.
+use super::{Divisible, Polygonal};
+use crate::geometry::polygon as geometry;
+use nannou::geom::{pt2, Point2};
+
+/// Enumeration of the ways in which the children of a tile can fail to tile their parent.
+///
+/// Children are referred to by their index in the vec returned by [`Divisible::divide`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TilingDefect {
+    /// The areas of the children don't sum up to the area of the parent.
+    AreaMismatch { parent: f32, children: f32 },
+    /// The interiors of two children intersect.
+    Overlap { first: usize, second: usize },
+    /// A point of the parent isn't covered by any child.
+    Gap { point: Point2 },
+    /// A child covers a point outside of the parent.
+    OutsideParent { child: usize, point: Point2 },
+}
+
+/// The defects found when dividing one of the tiles of a tiling.
+#[derive(Debug, Clone)]
+pub struct DivisionReport {
+    /// The number of divisions which produced the offending tile, 0 for the initial tiles.
+    pub depth: u8,
+    /// The index of the offending tile among the tiles at its depth.
+    pub index: usize,
+    pub defects: Vec<TilingDefect>,
+}
+
+/// Divides the tile and checks whether its children tile it, see [`verify_children`].
+pub fn verify_division<T>(tile: &T, resolution: usize) -> Vec<TilingDefect>
+where
+    T: Divisible + Polygonal,
+{
+    verify_children(tile, &tile.divide(), resolution)
+}
+
+/// Checks whether the children, which the tile was divided into, tile it, sampling coverage on a
+/// grid with resolution points along each side of the bounding box of the tile and its children.
+/// Returns all defects found, or an empty vec for a valid division.
+pub fn verify_children<T>(tile: &T, children: &[T], resolution: usize) -> Vec<TilingDefect>
+where
+    T: Polygonal,
+{
+    let parent = tile.vertices();
+    let children: Vec<Vec<Point2>> = children.iter().map(|c| c.vertices()).collect();
+
+    let tolerance = {
+        let bounds = geometry::bounds(&parent);
+        bounds.w().max(bounds.h()) * 1e-4
+    };
+
+    let mut defects = vec![];
+
+    let parent_area = geometry::signed_area(&parent).abs();
+    let children_area = children
+        .iter()
+        .map(|child| geometry::signed_area(child).abs())
+        .sum::<f32>();
+
+    // Areas scale with the square of the size, and so does the tolerance.
+    if (parent_area - children_area).abs() > parent_area * 1e-3 {
+        defects.push(TilingDefect::AreaMismatch {
+            parent: parent_area,
+            children: children_area,
+        });
+    }
+
+    let mut overlaps = vec![];
+
+    for first in 0..children.len() {
+        for second in first + 1..children.len() {
+            if edges_cross(&children[first], &children[second], tolerance) {
+                overlaps.push((first, second));
+            }
+        }
+    }
+
+    // Points close to any outline are skipped, where rounding errors decide on which side of an
+    // edge they fall.
+    let is_clear = |vertices: &[Point2], point: &Point2| {
+        (geometry::closest_point(vertices, point) - *point).magnitude() > tolerance
+    };
+
+    // The grid spans the children as well, to find those which stick out of the parent.
+    let bounds = geometry::bounds(
+        &children
+            .iter()
+            .flatten()
+            .chain(&parent)
+            .copied()
+            .collect::<Vec<_>>(),
+    );
+
+    let (step_x, step_y) = (
+        bounds.w() / resolution as f32,
+        bounds.h() / resolution as f32,
+    );
+
+    for i in 0..resolution {
+        for j in 0..resolution {
+            let point = pt2(
+                bounds.left() + (i as f32 + 0.5) * step_x,
+                bounds.bottom() + (j as f32 + 0.5) * step_y,
+            );
+
+            if !is_clear(&parent, &point) || children.iter().any(|c| !is_clear(c, &point)) {
+                continue;
+            }
+
+            let covering: Vec<usize> = (0..children.len())
+                .filter(|&child| geometry::contains(&children[child], &point))
+                .collect();
+
+            match (geometry::contains(&parent, &point), covering.len()) {
+                (true, 0) => defects.push(TilingDefect::Gap { point }),
+                (false, count) if count > 0 => defects.push(TilingDefect::OutsideParent {
+                    child: covering[0],
+                    point,
+                }),
+                _ => {}
+            }
+
+            for (k, &first) in covering.iter().enumerate() {
+                for &second in &covering[k + 1..] {
+                    overlaps.push((first, second));
+                }
+            }
+        }
+    }
+
+    overlaps.sort_unstable();
+    overlaps.dedup();
+
+    defects.extend(
+        overlaps
+            .into_iter()
+            .map(|(first, second)| TilingDefect::Overlap { first, second }),
+    );
+
+    defects
+}
+
+/// Divides the tiles for the given number of steps, just like [`super::create_tiling`], and
+/// verifies every division along the way, see [`verify_children`]. Leaves are kept as they are,
+/// without being verified. Returns a report for every tile whose division has defects.
+pub fn verify_tiling<T>(mut tiles: Vec<T>, steps: u8, resolution: usize) -> Vec<DivisionReport>
+where
+    T: Divisible + Polygonal,
+{
+    let mut reports = vec![];
+
+    for depth in 0..steps {
+        let mut divided_tiles = vec![];
+
+        for (index, tile) in tiles.into_iter().enumerate() {
+            if tile.is_leaf() {
+                divided_tiles.push(tile);
+                continue;
+            }
+
+            let children = tile.divide();
+            let defects = verify_children(&tile, &children, resolution);
+
+            if !defects.is_empty() {
+                reports.push(DivisionReport {
+                    depth,
+                    index,
+                    defects,
+                });
+            }
+
+            divided_tiles.extend(children);
+        }
+
+        tiles = divided_tiles;
+    }
+
+    reports
+}
+
+/// Checks whether an edge of one polygon crosses an edge of the other, each passing through the
+/// interior of the other edge. Edges which touch or run along each other don't cross.
+fn edges_cross(first: &[Point2], second: &[Point2], tolerance: f32) -> bool {
+    let edges = |vertices: &[Point2]| {
+        (0..vertices.len())
+            .map(|i| (vertices[i], vertices[(i + 1) % vertices.len()]))
+            .collect::<Vec<_>>()
+    };
+
+    let second_edges = edges(second);
+
+    edges(first).iter().any(|&(a, b)| {
+        second_edges.iter().any(|&(c, d)| {
+            // The signed distances of each segment's ends from the line through the other one.
+            let side = |p: Point2, q: Point2, r: Point2| {
+                let pq = q - p;
+                (pq.x * (r.y - p.y) - pq.y * (r.x - p.x)) / pq.magnitude()
+            };
+
+            let (c_side, d_side) = (side(a, b, c), side(a, b, d));
+            let (a_side, b_side) = (side(c, d, a), side(c, d, b));
+
+            c_side * d_side < 0.0
+                && a_side * b_side < 0.0
+                && c_side.abs().min(d_side.abs()) > tolerance
+                && a_side.abs().min(b_side.abs()) > tolerance
+        })
+    })
+}
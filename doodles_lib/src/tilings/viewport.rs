@@ -0,0 +1,89 @@
+//! Implementation of a lazy tiling which is only divided where it can be seen.
+//!
+//! [`super::create_tiling`] divides every tile the same number of times, which means 4^n tiles
+//! for n steps of a domino or wanderer tiling, most of which might be off-screen or smaller than
+//! a pixel. A [`ViewportTiling`] skips the tiles which don't intersect a viewport rect and stops
+//! dividing tiles once they are smaller than a minimum size, creating the tiles one by one while
+//! it is iterated.
+use super::{Divisible, Polygonal};
+use nannou::geom::Rect;
+
+/// An iterator over the tiles of a tiling which intersect a viewport.
+///
+/// Every tile is divided until its bounding box is at most min_size wide and high, or until it
+/// is a leaf, see [`Divisible::is_leaf`]. The viewport
+/// and the minimum size are both in the coordinates of the tiles, so to zoom in on a tiling which
+/// is drawn onto a window of N pixels, shrink the viewport and pass its width divided by N as the
+/// minimum size to divide the tiles down to single pixels.
+#[derive(Debug, Clone)]
+pub struct ViewportTiling<T> {
+    viewport: Rect,
+    min_size: f32,
+    max_steps: u8,
+    stack: Vec<(T, u8)>,
+}
+
+impl<T> ViewportTiling<T>
+where
+    T: Divisible + Polygonal,
+{
+    /// Constructs a new instance of [`ViewportTiling`] from the initial tiles.
+    ///
+    /// Panics if the minimum size isn't positive, since no tile would ever get small enough.
+    pub fn new(tiles: Vec<T>, viewport: Rect, min_size: f32) -> Self {
+        assert!(min_size > 0.0, "The minimum size must be positive.");
+
+        Self {
+            viewport,
+            min_size,
+            max_steps: u8::MAX,
+            stack: tiles.into_iter().rev().map(|tile| (tile, 0)).collect(),
+        }
+    }
+
+    /// Limits the number of times a tile is divided, even if it's larger than the minimum size.
+    pub fn with_max_steps(mut self, max_steps: u8) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    fn is_visible(&self, bounds: &Rect) -> bool {
+        bounds.left() < self.viewport.right()
+            && bounds.right() > self.viewport.left()
+            && bounds.bottom() < self.viewport.top()
+            && bounds.top() > self.viewport.bottom()
+    }
+}
+
+impl<T> Iterator for ViewportTiling<T>
+where
+    T: Divisible + Polygonal,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((tile, depth)) = self.stack.pop() {
+            let bounds = tile.bounds();
+
+            if !self.is_visible(&bounds) {
+                continue;
+            }
+
+            if tile.is_leaf()
+                || depth >= self.max_steps
+                || bounds.w().max(bounds.h()) <= self.min_size
+            {
+                return Some(tile);
+            }
+
+            self.stack.extend(
+                tile.divide()
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, depth + 1)),
+            );
+        }
+
+        None
+    }
+}
@@ -3,8 +3,8 @@
 //! This tilling uses a square proto-tile which has a reflection (handedness) and an orientation.
 //! For the purposes of this implementation both of these concepts are
 //! represented as enums, [`WandererTile`] and [`WandererTileOrientation`] respectively.
-use super::{Divisible, Rectangular};
-use nannou::geom::Rect;
+use super::{polygon::rect_vertices, Divisible, Labelled, Polygonal, Rectangular};
+use nannou::geom::{Point2, Rect};
 
 #[derive(Debug)]
 pub enum WandererTileOrientation {
@@ -120,3 +120,26 @@ impl Rectangular for WandererTile {
         }
     }
 }
+
+impl Polygonal for WandererTile {
+    fn vertices(&self) -> Vec<Point2> {
+        rect_vertices(self.rect())
+    }
+}
+
+impl Labelled for WandererTile {
+    fn label(&self) -> &str {
+        use WandererTileOrientation::{Bottom, Left, Right, Top};
+
+        match self {
+            WandererTile::LeftHanded(_, Bottom) => "left-handed-bottom",
+            WandererTile::LeftHanded(_, Left) => "left-handed-left",
+            WandererTile::LeftHanded(_, Top) => "left-handed-top",
+            WandererTile::LeftHanded(_, Right) => "left-handed-right",
+            WandererTile::RightHanded(_, Bottom) => "right-handed-bottom",
+            WandererTile::RightHanded(_, Left) => "right-handed-left",
+            WandererTile::RightHanded(_, Top) => "right-handed-top",
+            WandererTile::RightHanded(_, Right) => "right-handed-right",
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use doodles_lib::algorithms::flowfield::{
+    snapshot::{Recorder, Replay, Snapshot},
+    Flowfield, Source, VectorField,
+};
+use nannou::geom::{pt2, Rect, Vector2};
+use std::io::{Cursor, ErrorKind};
+
+/// Fills the grid with vectors which rotate by the angle on every update.
+struct Rotating {
+    angle: f32,
+    step: f32,
+}
+
+impl Source for Rotating {
+    fn fill(&mut self, vectors: &mut [Vector2], _rows: u32, _columns: u32) {
+        for (index, vector) in vectors.iter_mut().enumerate() {
+            *vector = Vector2::from_angle(self.angle + index as f32 * 0.1);
+        }
+
+        self.angle += self.step;
+    }
+}
+
+fn flowfield(step: f32) -> Flowfield<Rotating> {
+    let mut flowfield = Flowfield::new(
+        Rect::from_w_h(200.0, 100.0),
+        Rotating { angle: 0.0, step },
+        10,
+    );
+    flowfield.update();
+    flowfield
+}
+
+#[test]
+fn saved_snapshots_are_loaded_unchanged() {
+    let snapshot = flowfield(0.5).snapshot();
+    let path = std::env::temp_dir().join(format!("doodles_{}.flow", std::process::id()));
+
+    snapshot.save(&path).unwrap();
+    let loaded = Snapshot::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let loaded = loaded.unwrap();
+
+    assert_eq!(loaded, snapshot);
+    assert_eq!(loaded.difference(&snapshot), Some(0.0));
+    assert_eq!(
+        loaded.sample(&pt2(-95.0, 45.0)),
+        snapshot.sample(&pt2(-95.0, 45.0))
+    );
+}
+
+#[test]
+fn snapshots_of_different_fields_are_told_apart() {
+    let mut first = flowfield(0.5);
+    let second = flowfield(0.5);
+
+    assert_eq!(first.snapshot().difference(&second.snapshot()), Some(0.0));
+
+    first.update();
+
+    let difference = first.snapshot().difference(&second.snapshot()).unwrap();
+    assert!(difference > 0.4, "difference: {}", difference);
+
+    let coarse = Flowfield::new(
+        Rect::from_w_h(200.0, 100.0),
+        Rotating {
+            angle: 0.0,
+            step: 0.5,
+        },
+        20,
+    );
+    assert_eq!(first.snapshot().difference(&coarse.snapshot()), None);
+}
+
+#[test]
+fn recorded_time_series_are_replayed_frame_by_frame() {
+    let mut flowfield = flowfield(0.25);
+    let mut snapshots = vec![flowfield.snapshot()];
+
+    let mut recorder = Recorder::new(vec![], &snapshots[0]).unwrap();
+
+    for _ in 0..3 {
+        flowfield.update();
+        snapshots.push(flowfield.snapshot());
+        recorder.record(snapshots.last().unwrap()).unwrap();
+    }
+
+    let bytes = recorder.finish().unwrap();
+    let replayed: Vec<Snapshot> = Replay::new(Cursor::new(bytes)).unwrap().collect();
+
+    assert_eq!(replayed, snapshots);
+}
+
+#[test]
+fn oversized_grids_are_rejected() {
+    let mut bytes = vec![];
+    bytes.extend_from_slice(b"FLOW");
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+
+    for edge in [-100.0f32, -50.0, 100.0, 50.0].iter() {
+        bytes.extend_from_slice(&edge.to_le_bytes());
+    }
+
+    for value in [10u32, u32::MAX, 2].iter() {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    bytes.push(0);
+
+    let path = std::env::temp_dir().join(format!("doodles_{}_oversized.flow", std::process::id()));
+    std::fs::write(&path, bytes).unwrap();
+    let error = Snapshot::load(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
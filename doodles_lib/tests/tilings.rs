@@ -0,0 +1,242 @@
+use doodles_lib::tilings::{
+    create_adaptive_tiling,
+    domino::DominoTile,
+    monotile::{Monotile, MonotileTile},
+    penrose::{PenroseTile, PenroseVariant},
+    pinwheel::{Chirality, PinwheelTile},
+    polygon::Polygon,
+    substitution::{SubstitutionRules, SubstitutionTile},
+    verification::{verify_children, verify_division, verify_tiling, DivisionReport, TilingDefect},
+    viewport::ViewportTiling,
+    wanderer::{WandererTile, WandererTileOrientation},
+    Divisible, Polygonal,
+};
+use nannou::geom::{pt2, Point2, Rect};
+use std::rc::Rc;
+
+const RESOLUTION: usize = 32;
+
+/// A rect which is divided into the given rects, valid or not.
+struct RectTile {
+    rect: Rect,
+    children: Vec<Rect>,
+}
+
+impl Divisible for RectTile {
+    fn divide(&self) -> Vec<Self> {
+        self.children
+            .iter()
+            .map(|&rect| RectTile {
+                rect,
+                children: vec![rect],
+            })
+            .collect()
+    }
+}
+
+impl Polygonal for RectTile {
+    fn vertices(&self) -> Vec<Point2> {
+        Polygon::from_rect(&self.rect).vertices()
+    }
+}
+
+fn assert_valid(reports: Vec<DivisionReport>) {
+    assert!(reports.is_empty(), "invalid divisions: {:#?}", reports);
+}
+
+#[test]
+fn domino_tiles_are_divided_into_valid_tilings() {
+    let rect = Rect::from_x_y_w_h(10.0, -20.0, 400.0, 200.0);
+
+    assert_valid(verify_tiling(
+        vec![DominoTile::Horizontal(rect)],
+        4,
+        RESOLUTION,
+    ));
+    assert_valid(verify_tiling(
+        vec![DominoTile::Vertical(Rect::from_w_h(200.0, 400.0))],
+        4,
+        RESOLUTION,
+    ));
+}
+
+#[test]
+fn wanderer_tiles_are_divided_into_valid_tilings() {
+    let rect = Rect::from_w_h(256.0, 256.0);
+
+    let orientations = || {
+        vec![
+            WandererTileOrientation::Left,
+            WandererTileOrientation::Right,
+            WandererTileOrientation::Top,
+            WandererTileOrientation::Bottom,
+        ]
+    };
+
+    for (left_handed, right_handed) in orientations().into_iter().zip(orientations()) {
+        let tiles = vec![
+            WandererTile::LeftHanded(rect, left_handed),
+            WandererTile::RightHanded(rect, right_handed),
+        ];
+
+        assert_valid(verify_tiling(tiles, 3, RESOLUTION));
+    }
+}
+
+#[test]
+fn penrose_tiles_are_divided_into_valid_tilings() {
+    for variant in [PenroseVariant::KitesAndDarts, PenroseVariant::Rhombs].iter() {
+        let mut tiles = PenroseTile::sun(*variant, pt2(0.0, 0.0), 100.0);
+        tiles.extend(PenroseTile::star(*variant, pt2(300.0, 0.0), 100.0));
+
+        assert_valid(verify_tiling(tiles, 4, RESOLUTION));
+    }
+}
+
+#[test]
+fn pinwheel_tiles_are_divided_into_valid_tilings() {
+    let rect = Rect::from_w_h(200.0, 100.0);
+
+    for chirality in [Chirality::LeftHanded, Chirality::RightHanded].iter() {
+        assert_valid(verify_tiling(
+            PinwheelTile::from_rect(&rect, *chirality),
+            3,
+            RESOLUTION,
+        ));
+    }
+}
+
+#[test]
+fn substitution_rules_divide_into_valid_tilings() {
+    let rect = Rect::from_w_h(256.0, 256.0);
+
+    for rules in vec![SubstitutionRules::domino(), SubstitutionRules::wanderer()] {
+        let rules = Rc::new(rules);
+        let labels: Vec<String> = rules.labels().map(String::from).collect();
+
+        let tiles = labels
+            .iter()
+            .filter_map(|label| SubstitutionTile::from_rect(&rules, label, &rect))
+            .collect();
+
+        assert_valid(verify_tiling(tiles, 3, RESOLUTION));
+    }
+}
+
+#[test]
+fn monotiles_of_a_patch_do_not_overlap() {
+    // The outlines of the supertiles only approximate their monotiles, so gaps and monotiles
+    // sticking out of the seed are expected, but no two monotiles may overlap.
+    for monotile in [Monotile::Hat, Monotile::Spectre].iter() {
+        let seed = MonotileTile::seed(*monotile, 2, pt2(0.0, 0.0), 10.0);
+        let patch = MonotileTile::patch(*monotile, 2, pt2(0.0, 0.0), 10.0);
+
+        assert!(patch.iter().all(MonotileTile::is_monotile));
+        assert!(patch.iter().any(MonotileTile::is_reflected) == (*monotile == Monotile::Hat));
+
+        let overlaps: Vec<TilingDefect> = verify_children(&seed[0], &patch, 4 * RESOLUTION)
+            .into_iter()
+            .filter(|defect| matches!(defect, TilingDefect::Overlap { .. }))
+            .collect();
+
+        assert!(overlaps.is_empty(), "overlapping monotiles: {:?}", overlaps);
+    }
+}
+
+#[test]
+fn monotiles_are_not_divided_any_further() {
+    let seed = || MonotileTile::seed(Monotile::Hat, 2, pt2(0.0, 0.0), 10.0);
+    let patch = MonotileTile::patch(Monotile::Hat, 2, pt2(0.0, 0.0), 10.0);
+
+    let adaptive = create_adaptive_tiling(seed(), |_, _| true);
+    assert_eq!(adaptive.len(), patch.len());
+
+    let viewport = ViewportTiling::new(seed(), Rect::from_w_h(1000.0, 1000.0), 1.0);
+    assert_eq!(viewport.count(), patch.len());
+
+    assert_valid(verify_tiling(patch, 3, RESOLUTION));
+}
+
+#[test]
+fn gaps_are_reported() {
+    let tile = RectTile {
+        rect: Rect::from_w_h(2.0, 2.0),
+        children: vec![
+            Rect::from_x_y_w_h(-0.5, 0.0, 1.0, 2.0),
+            Rect::from_x_y_w_h(0.5, 0.5, 1.0, 1.0),
+        ],
+    };
+
+    let defects = verify_division(&tile, RESOLUTION);
+
+    assert!(defects
+        .iter()
+        .any(|defect| matches!(defect, TilingDefect::AreaMismatch { .. })));
+    assert!(defects.iter().any(|defect| match defect {
+        TilingDefect::Gap { point } => point.x > 0.0 && point.y < 0.0,
+        _ => false,
+    }));
+}
+
+#[test]
+fn overlaps_are_reported() {
+    // The areas sum up, but the second child covers part of the first instead of the gap.
+    let tile = RectTile {
+        rect: Rect::from_w_h(2.0, 2.0),
+        children: vec![
+            Rect::from_x_y_w_h(-0.5, 0.0, 1.0, 2.0),
+            Rect::from_x_y_w_h(0.5, 0.5, 1.0, 1.0),
+            Rect::from_x_y_w_h(0.0, 0.5, 1.0, 1.0),
+        ],
+    };
+
+    let defects = verify_division(&tile, RESOLUTION);
+
+    assert!(defects.contains(&TilingDefect::Overlap {
+        first: 0,
+        second: 2
+    }));
+    assert!(defects.contains(&TilingDefect::Overlap {
+        first: 1,
+        second: 2
+    }));
+    assert!(!defects.contains(&TilingDefect::Overlap {
+        first: 0,
+        second: 1
+    }));
+}
+
+#[test]
+fn children_outside_of_the_parent_are_reported() {
+    let tile = RectTile {
+        rect: Rect::from_w_h(2.0, 2.0),
+        children: vec![
+            Rect::from_x_y_w_h(-0.5, 0.0, 1.0, 2.0),
+            Rect::from_x_y_w_h(1.0, 0.0, 2.0, 2.0),
+        ],
+    };
+
+    let defects = verify_division(&tile, RESOLUTION);
+
+    assert!(defects.iter().any(|defect| match defect {
+        TilingDefect::OutsideParent { child, point } => *child == 1 && point.x > 1.0,
+        _ => false,
+    }));
+}
+
+#[test]
+fn offending_tiles_are_reported_by_depth_and_index() {
+    let valid = RectTile {
+        rect: Rect::from_w_h(2.0, 2.0),
+        children: vec![Rect::from_w_h(2.0, 2.0)],
+    };
+    let invalid = RectTile {
+        rect: Rect::from_x_y_w_h(4.0, 0.0, 2.0, 2.0),
+        children: vec![Rect::from_x_y_w_h(4.0, 0.0, 1.0, 1.0)],
+    };
+
+    let reports = verify_tiling(vec![valid, invalid], 2, RESOLUTION);
+
+    assert_eq!(reports.len(), 1);
+    assert_eq!((reports[0].depth, reports[0].index), (0, 1));
+}
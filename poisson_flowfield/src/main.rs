@@ -42,7 +42,7 @@ struct Point {
 
 struct Model {
     canvas: Ellipse,
-    flowfield: Flowfield<Perlin>,
+    flowfield: Flowfield<Noise<Perlin>>,
     particles: Vec<Particle>,
     poissonfield: Vec<Point>,
     should_draw_particles: bool,
@@ -52,7 +52,7 @@ struct Model {
 impl Model {
     fn new(
         canvas: Ellipse,
-        flowfield: Flowfield<Perlin>,
+        flowfield: Flowfield<Noise<Perlin>>,
         particles: Vec<Particle>,
         poissonfield: Vec<Point>,
         should_draw_particles: bool,